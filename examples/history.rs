@@ -0,0 +1,35 @@
+//! Write the sort history image of a run to a PNG file, without a browser.
+//!
+//! ```sh
+//! cargo run --example history -- merge 200 shuffled
+//! ```
+
+use leptos_sort::visual_sort::input::{self, Distribution};
+use leptos_sort::visual_sort::{image, Sort, SortBase};
+
+const ROWS: usize = 600;
+const WIDTH: usize = 1200;
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let name = args.next().unwrap_or_else(|| "merge".into());
+    let sort = Sort::from_route(&name).unwrap_or_else(|| {
+        let names: Vec<&str> = Sort::ALL.iter().map(|s| &s.route_as_str()[1..]).collect();
+        panic!("unknown sort {name}, expected one of {}", names.join(", "))
+    });
+    let items = args
+        .next()
+        .map_or(200, |n| n.parse().expect("items to be a number"));
+    let distribution = args.next().map_or(Distribution::Shuffled, |key| {
+        Distribution::from_key(&key).expect("a known distribution")
+    });
+
+    let data = distribution.generate(items, input::random_seed());
+    let picture = image::history(sort, SortBase::new(data), ROWS, WIDTH);
+    let path = format!("{}-history.png", &sort.route_as_str()[1..]);
+    std::fs::write(&path, picture.png()).expect("image to be written");
+    println!(
+        "{} rows of {} pixels written to {path}",
+        picture.height, picture.width
+    );
+}
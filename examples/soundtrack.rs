@@ -0,0 +1,37 @@
+//! Write the soundtrack of a run to a WAV file, without a browser.
+//!
+//! ```sh
+//! cargo run --example soundtrack -- merge 200 400
+//! ```
+
+use leptos_sort::visual_sort::input::{self, Distribution};
+use leptos_sort::visual_sort::soundtrack::Synth;
+use leptos_sort::visual_sort::{Sort, SortBase};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let name = args.next().unwrap_or_else(|| "merge".into());
+    let sort = Sort::from_route(&name).unwrap_or_else(|| {
+        let names: Vec<&str> = Sort::ALL.iter().map(|s| &s.route_as_str()[1..]).collect();
+        panic!("unknown sort {name}, expected one of {}", names.join(", "))
+    });
+    let items = args
+        .next()
+        .map_or(200, |n| n.parse().expect("items to be a number"));
+    let steps_per_second = args.next().map_or(400.0, |n| {
+        n.parse()
+            .ok()
+            .filter(|&rate: &f64| rate.is_finite() && rate > 0.0)
+            .expect("steps per second to be a positive number")
+    });
+
+    let data = Distribution::Shuffled.generate(items, input::random_seed());
+    let synth = Synth {
+        steps_per_second,
+        ..Synth::default()
+    };
+    let soundtrack = synth.render(sort, SortBase::new(data));
+    let path = format!("{}.wav", &sort.route_as_str()[1..]);
+    std::fs::write(&path, soundtrack.wav()).expect("sound to be written");
+    println!("{:.1} seconds written to {path}", soundtrack.duration());
+}
@@ -0,0 +1,161 @@
+use leptos::*;
+use leptos_sort::visual_sort::pitch::Pitch;
+use leptos_sort::visual_sort::sound::{schedule, Note, Waveform, MAX_SPAN};
+use leptos_sort::visual_sort::soundtrack::Soundtrack;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::JsValue;
+use web_sys::{AudioContext, Blob, BlobPropertyBag, GainNode, OscillatorType, Url};
+
+/// Sound settings chosen in the controls, shared by every page.
+#[derive(Copy, Clone)]
+pub struct Sound {
+    pub volume: RwSignal<f32>,
+    pub waveform: RwSignal<Waveform>,
+    pub muted: RwSignal<bool>,
+    pub pitch: RwSignal<Pitch>,
+}
+
+impl Sound {
+    pub fn new() -> Self {
+        Self {
+            volume: create_rw_signal(0.1),
+            waveform: create_rw_signal(Waveform::Sine),
+            muted: create_rw_signal(false),
+            pitch: create_rw_signal(Pitch::default()),
+        }
+    }
+}
+
+/// The app's one audio context, sounding the operations of whichever sort is running.
+///
+/// Each note gets oscillators of its own, shaped by the envelope of its voice and placed
+/// left to right by where in the data it was, all passing through one volume.
+pub struct Audio {
+    ctx: AudioContext,
+    sound: Sound,
+    volume: GainNode,
+    /// Every note passes through here, replaced to cut off notes still scheduled.
+    bus: RefCell<GainNode>,
+    /// Audio time of the latest notes, their span is the time since.
+    last: Cell<f64>,
+}
+
+impl Audio {
+    /// Set up the audio graph, following the settings for as long as the app runs.
+    pub fn new(sound: Sound) -> Self {
+        let ctx = AudioContext::new().expect("to create audio context");
+        let volume = ctx.create_gain().expect("to create gain");
+        volume.gain().set_value(0.0);
+        volume
+            .connect_with_audio_node(&ctx.destination())
+            .expect("volume connect destination");
+        let bus = ctx.create_gain().expect("to create gain");
+        bus.connect_with_audio_node(&volume)
+            .expect("bus connect volume");
+
+        let gain = volume.clone();
+        create_effect(move |_| {
+            let level = if sound.muted.get() {
+                0.0
+            } else {
+                sound.volume.get()
+            };
+            gain.gain().set_value(level);
+        });
+
+        Self {
+            last: Cell::new(ctx.current_time()),
+            ctx,
+            sound,
+            volume,
+            bus: RefCell::new(bus),
+        }
+    }
+
+    /// Sound the notes of a frame one after the other, over as long as the frame took.
+    ///
+    /// Every operation is heard as a note of its own rather than only the last.
+    pub fn play(&self, notes: &[Note]) {
+        let now = self.ctx.current_time();
+        let span = (now - self.last.replace(now)).clamp(0.0, MAX_SPAN);
+        let waveform = oscillator_type(self.sound.waveform.get_untracked());
+        let pitch = self.sound.pitch.get_untracked();
+        let bus = self.bus.borrow();
+        for (at, note, length) in schedule(notes, span) {
+            let _ = self.voice(note, waveform, pitch, now + at, length, &bus);
+        }
+    }
+
+    /// Nodes of one note from start to silence, left to be collected once stopped
+    fn voice(
+        &self,
+        note: &Note,
+        waveform: OscillatorType,
+        pitch: Pitch,
+        at: f64,
+        length: f64,
+        bus: &GainNode,
+    ) -> Result<(), JsValue> {
+        let panner = self.ctx.create_stereo_panner()?;
+        panner.pan().set_value(note.pan as f32);
+        panner.connect_with_audio_node(bus)?;
+
+        let envelope = self.ctx.create_gain()?;
+        envelope.connect_with_audio_node(&panner)?;
+        let level = note.voice.level();
+        let [(_, start), shape @ ..] = note.voice.envelope().breakpoints(length);
+        envelope
+            .gain()
+            .set_value_at_time((start * level) as f32, at)?;
+        for (t, shaped) in shape {
+            envelope
+                .gain()
+                .linear_ramp_to_value_at_time((shaped * level) as f32, at + t)?;
+        }
+
+        for &fraction in &note.fractions {
+            let osc = self.ctx.create_oscillator()?;
+            osc.set_type(waveform);
+            osc.frequency().set_value(pitch.hz(fraction) as f32);
+            osc.connect_with_audio_node(&envelope)?;
+            osc.start_with_when(at)?;
+            osc.stop_with_when(at + length)?;
+        }
+        Ok(())
+    }
+
+    /// Let the audio context run, it can only start after the user interacted with the page.
+    pub fn resume(&self) {
+        let _ = self.ctx.resume();
+    }
+
+    /// Cut any notes still scheduled, when a run pauses, ends or leaves the page.
+    pub fn silence(&self) {
+        let bus = self.ctx.create_gain().expect("to create gain");
+        bus.connect_with_audio_node(&self.volume)
+            .expect("bus connect volume");
+        let _ = self.bus.replace(bus).disconnect();
+        self.last.set(self.ctx.current_time());
+    }
+}
+
+fn oscillator_type(waveform: Waveform) -> OscillatorType {
+    match waveform {
+        Waveform::Sine => OscillatorType::Sine,
+        Waveform::Square => OscillatorType::Square,
+        Waveform::Sawtooth => OscillatorType::Sawtooth,
+        Waveform::Triangle => OscillatorType::Triangle,
+    }
+}
+
+/// Object URL of the soundtrack as a WAV file, to be revoked once no longer linked.
+pub fn wav_url(soundtrack: &Soundtrack) -> String {
+    let bytes = js_sys::Uint8Array::from(soundtrack.wav().as_slice());
+    let parts = js_sys::Array::of1(&bytes);
+    let blob = Blob::new_with_u8_array_sequence_and_options(
+        &parts,
+        BlobPropertyBag::new().type_("audio/wav"),
+    )
+    .expect("to create wav blob");
+    Url::create_object_url_with_blob(&blob).expect("to create wav url")
+}
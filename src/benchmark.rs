@@ -0,0 +1,470 @@
+use crate::Callback;
+use leptos::*;
+use leptos_router::*;
+use leptos_sort::visual_sort::benchmark::{Benchmark, Measurement, DISTRIBUTIONS, SIZES};
+use leptos_sort::visual_sort::complexity::{self, Fit, Sample};
+use leptos_sort::visual_sort::input::Distribution;
+use leptos_sort::visual_sort::Sort;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use web_sys::CanvasRenderingContext2d;
+
+pub const ROUTE: &str = "/benchmark";
+
+/// Steps worked between checks of the frame time
+pub const STEPS_PER_CHUNK: usize = 5_000;
+
+/// Time spent working each frame, leaving the rest for the page
+pub const FRAME_MS: f64 = 12.0;
+
+/// Line colors of the sorts in the chart, in the order of [`Sort::ALL`]
+const PALETTE: [&str; 8] = [
+    "#dc3545", "#fd7e14", "#ffc107", "#198754", "#20c997", "#0dcaf0", "#0d6efd", "#d63384",
+];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Column {
+    Sort,
+    Input,
+    Items,
+    Steps,
+    Accesses,
+    Updates,
+    Comparisons,
+}
+
+impl Column {
+    const ALL: [Column; 7] = [
+        Self::Sort,
+        Self::Input,
+        Self::Items,
+        Self::Steps,
+        Self::Accesses,
+        Self::Updates,
+        Self::Comparisons,
+    ];
+
+    /// Columns that can be charted against the number of items
+    const METRICS: [Column; 4] = [
+        Self::Steps,
+        Self::Accesses,
+        Self::Updates,
+        Self::Comparisons,
+    ];
+
+    fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Sort => "Sort",
+            Self::Input => "Input",
+            Self::Items => "Items",
+            Self::Steps => "Steps",
+            Self::Accesses => "Accesses",
+            Self::Updates => "Updates",
+            Self::Comparisons => "Comparisons",
+        }
+    }
+
+    fn value(&self, m: &Measurement) -> usize {
+        match self {
+            Self::Sort | Self::Input => 0,
+            Self::Items => m.items,
+            Self::Steps => m.steps,
+            Self::Accesses => m.counters.accesses,
+            Self::Updates => m.counters.updates,
+            Self::Comparisons => m.counters.comparisons,
+        }
+    }
+
+    fn sample(&self, m: &Measurement) -> Sample {
+        Sample {
+            n: m.items as f64,
+            k: m.range as f64,
+            count: self.value(m) as f64,
+        }
+    }
+
+    fn cmp(&self, a: &Measurement, b: &Measurement) -> Ordering {
+        match self {
+            Self::Sort => a.sort.name_as_str().cmp(b.sort.name_as_str()),
+            Self::Input => a
+                .distribution
+                .name_as_str()
+                .cmp(b.distribution.name_as_str()),
+            _ => self.value(a).cmp(&self.value(b)),
+        }
+    }
+}
+
+#[component]
+pub fn BenchmarkPage(seed: RwSignal<u64>) -> impl IntoView {
+    let benchmark = store_value(None::<Benchmark>);
+    let results = create_rw_signal(Vec::<Measurement>::new());
+    let running = create_rw_signal(false);
+    let total = create_rw_signal(0);
+
+    let window = web_sys::window().unwrap();
+    let document = leptos::document();
+    let location = use_location();
+    let start_loc = location.pathname.get_untracked();
+
+    let measure: Callback = Rc::new(RefCell::new(Closure::new(move |_| ())));
+    let measure_clone = measure.clone();
+
+    *measure.borrow_mut() = Closure::new(move |_| {
+        let timeline = document.timeline();
+        let start = timeline.current_time().unwrap();
+        let mut measured = Vec::new();
+        let mut done = true;
+        benchmark.update_value(|benchmark| {
+            let Some(benchmark) = benchmark.as_mut() else {
+                return;
+            };
+            while !benchmark.done() && timeline.current_time().unwrap() - start < FRAME_MS {
+                measured.extend(benchmark.work(STEPS_PER_CHUNK));
+            }
+            done = benchmark.done();
+        });
+        if !measured.is_empty() {
+            results.update(|results| results.append(&mut measured));
+        }
+
+        if !done && running.get_untracked() && start_loc == location.pathname.get_untracked() {
+            let _ = window.request_animation_frame(measure_clone.borrow().as_ref().unchecked_ref());
+        } else {
+            running.set(false);
+        }
+    });
+
+    let window = web_sys::window().unwrap();
+    let start = move |_| {
+        let sweep = Benchmark::new(&Sort::ALL, &DISTRIBUTIONS, &SIZES, seed.get_untracked());
+        total.set(sweep.len());
+        benchmark.set_value(Some(sweep));
+        results.set(Vec::new());
+        running.set(true);
+        let _ = window.request_animation_frame(measure.borrow().as_ref().unchecked_ref());
+    };
+
+    view! {
+        <div class="container-fluid my-3 p-4">
+            <h3 class="p-2">Benchmark</h3>
+            <p class="ps-2 text-muted">
+                "Every sort on "{SIZES.map(|n| n.to_string()).join(", ")}
+                " items, without drawing or sound, using seed "{move || seed.get()}
+            </p>
+            <div class="d-flex justify-content-start align-items-center mb-3">
+                <button class="col-1 btn btn-outline-danger mx-2"
+                    disabled=move || running.get()
+                    on:click=start>
+                    <i class="bi bi-play-fill me-2"></i>
+                    Run
+                </button>
+                <button class="col-1 btn mx-2"
+                    disabled=move || !running.get()
+                    class:btn-outline-warning=move || running.get()
+                    class:btn-outline-secondary=move || !running.get()
+                    on:click=move |_| running.set(false)>
+                    <i class="bi bi-stop-fill me-2"></i>
+                    Stop
+                </button>
+                <span class="text-muted ms-2">
+                    {move || results.with(Vec::len)}" / "{move || total.get()}" measured"
+                </span>
+            </div>
+            <Chart results/>
+            <ResultTable results/>
+        </div>
+    }
+}
+
+#[component]
+fn Chart(results: RwSignal<Vec<Measurement>>) -> impl IntoView {
+    let distribution = create_rw_signal(Distribution::Shuffled);
+    let metric = create_rw_signal(Column::Comparisons);
+    let canvas_ref = create_node_ref::<html::Canvas>();
+
+    // reference curves of each sort, the best first, and its growth exponent
+    let fits = create_memo(move |_| {
+        let (distribution, metric) = (distribution.get(), metric.get());
+        results.with(|results| {
+            Sort::ALL.map(|sort| {
+                let samples: Vec<Sample> = results
+                    .iter()
+                    .filter(|m| m.sort == sort && m.distribution == distribution)
+                    .map(|m| metric.sample(m))
+                    .collect();
+                (complexity::fits(&samples), complexity::exponent(&samples))
+            })
+        })
+    });
+
+    create_effect(move |_| {
+        let (distribution, metric) = (distribution.get(), metric.get());
+        results.with(|results| {
+            if let Some(canvas) = canvas_ref.get() {
+                draw_chart(&canvas, results, distribution, metric, &fits.get());
+            }
+        });
+    });
+
+    view! {
+        <div class="d-flex justify-content-start align-items-center px-2 mb-2">
+            <label class="text-muted me-2">"Input"</label>
+            <select class="form-select form-select-sm w-auto me-3"
+                on:change=move |ev| {
+                    if let Some(value) = Distribution::from_key(&event_target_value(&ev)) {
+                        distribution.set(value);
+                    }
+                }>
+                {DISTRIBUTIONS
+                    .into_iter()
+                    .map(|d| view! {
+                        <option value=d.key_as_str() prop:selected=move || distribution.get() == d>
+                            {d.name_as_str()}
+                        </option>
+                    })
+                    .collect_view()}
+            </select>
+            <label class="text-muted me-2">"Count"</label>
+            <select class="form-select form-select-sm w-auto"
+                on:change=move |ev| {
+                    let name = event_target_value(&ev);
+                    if let Some(value) = Column::METRICS.into_iter().find(|c| c.name_as_str() == name) {
+                        metric.set(value);
+                    }
+                }>
+                {Column::METRICS
+                    .into_iter()
+                    .map(|c| view! {
+                        <option value=c.name_as_str() prop:selected=move || metric.get() == c>
+                            {c.name_as_str()}
+                        </option>
+                    })
+                    .collect_view()}
+            </select>
+        </div>
+        <div class="d-flex justify-content-start p-2">
+            <canvas class="col-11 border border-1 rounded border-danger" style="height: 360px;"
+                _ref=canvas_ref />
+        </div>
+        <Growth fits/>
+    }
+}
+
+/// Best fitting curves of the charted counts, the wide faint lines in the chart.
+#[component]
+fn Growth(fits: Memo<[(Vec<Fit>, Option<f64>); 8]>) -> impl IntoView {
+    let rows = move || {
+        Sort::ALL
+            .into_iter()
+            .zip(fits.get())
+            .filter_map(|(sort, (fits, exponent))| Some((sort, *fits.first()?, exponent)))
+            .map(|(sort, fit, exponent)| {
+                view! {
+                    <tr>
+                        <td>{sort.name_as_str()}</td>
+                        <td>{exponent.map_or("-".to_string(), |b| format!("{b:.2}"))}</td>
+                        <td>{format!("{:.3} · {}", fit.scale, fit.curve.name_as_str())}</td>
+                        <td>{format!("{:.3}", fit.error)}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <table class="table table-sm col-11 mt-3">
+            <thead>
+                <tr>
+                    <th>"Sort"</th>
+                    <th title="b in count ≈ a·nᵇ">"Exponent"</th>
+                    <th>"Best fit"</th>
+                    <th title="RMS of log10 ratios to the curve">"Fit error"</th>
+                </tr>
+            </thead>
+            <tbody>{rows}</tbody>
+        </table>
+    }
+}
+
+/// Draw the metric of every sort against the number of items, both on log scales,
+/// with every reference curve fitted to each sort dashed behind it and the best one wide.
+fn draw_chart(
+    canvas: &web_sys::HtmlCanvasElement,
+    results: &[Measurement],
+    distribution: Distribution,
+    metric: Column,
+    fits: &[(Vec<Fit>, Option<f64>)],
+) {
+    const MARGIN: f64 = 50.0;
+
+    let canvas_w = canvas.client_width() as f64;
+    let canvas_h = canvas.client_height() as f64;
+    canvas.set_width(canvas_w as u32);
+    canvas.set_height(canvas_h as u32);
+    let ctx2d = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<CanvasRenderingContext2d>()
+        .expect("canvas to have 2d context");
+    ctx2d.clear_rect(0.0, 0.0, canvas_w, canvas_h);
+
+    let points: Vec<(Sort, f64, f64)> = results
+        .iter()
+        .filter(|m| m.distribution == distribution && metric.value(m) > 0)
+        .map(|m| {
+            (
+                m.sort,
+                (m.items as f64).log10(),
+                (metric.value(m) as f64).log10(),
+            )
+        })
+        .collect();
+
+    // whole decades on both axes
+    let x_min = (SIZES[0] as f64).log10().floor();
+    let x_max = (SIZES[SIZES.len() - 1] as f64).log10().ceil();
+    let y_max = points.iter().map(|p| p.2).fold(1.0, f64::max).ceil();
+    let plot_w = canvas_w - 2.0 * MARGIN;
+    let plot_h = canvas_h - 2.0 * MARGIN;
+    let x_at = |x: f64| MARGIN + (x - x_min) / (x_max - x_min) * plot_w;
+    let y_at = |y: f64| canvas_h - MARGIN - y / y_max * plot_h;
+
+    // decade grid and labels
+    ctx2d.set_line_width(1.0);
+    ctx2d.set_stroke_style(&JsValue::from("#495057"));
+    ctx2d.set_fill_style(&JsValue::from("#adb5bd"));
+    ctx2d.set_font("12px sans-serif");
+    for decade in x_min as i32..=x_max as i32 {
+        let x = x_at(decade as f64);
+        ctx2d.begin_path();
+        ctx2d.move_to(x, MARGIN);
+        ctx2d.line_to(x, canvas_h - MARGIN);
+        ctx2d.stroke();
+        let _ = ctx2d.fill_text(&format!("1e{decade}"), x - 10.0, canvas_h - MARGIN + 20.0);
+    }
+    for decade in 0..=y_max as i32 {
+        let y = y_at(decade as f64);
+        ctx2d.begin_path();
+        ctx2d.move_to(MARGIN, y);
+        ctx2d.line_to(canvas_w - MARGIN, y);
+        ctx2d.stroke();
+        let _ = ctx2d.fill_text(&format!("1e{decade}"), 10.0, y + 4.0);
+    }
+
+    // one line per sort, and its name in the legend
+    ctx2d.set_line_width(2.0);
+    for (i, sort) in Sort::ALL.into_iter().enumerate() {
+        let color = JsValue::from(PALETTE[i]);
+        ctx2d.set_stroke_style(&color);
+        ctx2d.set_fill_style(&color);
+        ctx2d.begin_path();
+        for (j, &(_, x, y)) in points.iter().filter(|p| p.0 == sort).enumerate() {
+            if j == 0 {
+                ctx2d.move_to(x_at(x), y_at(y));
+            } else {
+                ctx2d.line_to(x_at(x), y_at(y));
+            }
+        }
+        ctx2d.stroke();
+        let _ = ctx2d.fill_text(sort.name_as_str(), MARGIN + 10.0, MARGIN + 16.0 * i as f64);
+
+        // the best fit wide and faint, the others dashed and named where they end
+        let dashed = js_sys::Array::of2(&4.0.into(), &4.0.into());
+        for (rank, fit) in fits[i].0.iter().enumerate().rev() {
+            let best = rank == 0;
+            ctx2d.set_global_alpha(if best { 0.4 } else { 0.25 });
+            ctx2d.set_line_width(if best { 6.0 } else { 1.0 });
+            let _ = ctx2d.set_line_dash(&if best {
+                js_sys::Array::new().into()
+            } else {
+                dashed.clone().into()
+            });
+            ctx2d.begin_path();
+            let fitted: Vec<(f64, f64)> = results
+                .iter()
+                .filter(|m| m.sort == sort && m.distribution == distribution)
+                .map(|m| (m.items as f64, fit.at(m.items as f64, m.range as f64)))
+                .filter(|&(_, count)| count > 0.0)
+                .map(|(n, count)| (x_at(n.log10()), y_at(count.log10())))
+                .collect();
+            for (j, &(x, y)) in fitted.iter().enumerate() {
+                if j == 0 {
+                    ctx2d.move_to(x, y);
+                } else {
+                    ctx2d.line_to(x, y);
+                }
+            }
+            ctx2d.stroke();
+            if let (false, Some(&(x, y))) = (best, fitted.last()) {
+                let _ = ctx2d.fill_text(fit.curve.name_as_str(), x + 4.0, y + 4.0);
+            }
+        }
+        ctx2d.set_global_alpha(1.0);
+        ctx2d.set_line_width(2.0);
+        let _ = ctx2d.set_line_dash(&js_sys::Array::new());
+    }
+}
+
+#[component]
+fn ResultTable(results: RwSignal<Vec<Measurement>>) -> impl IntoView {
+    let order = create_rw_signal((Column::Items, true));
+
+    let rows = move || {
+        let (column, ascending) = order.get();
+        let mut rows = results.get();
+        rows.sort_by(|a, b| {
+            let ordering = column.cmp(a, b);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+        rows.into_iter()
+            .map(|m| {
+                view! {
+                    <tr>
+                        <td>{m.sort.name_as_str()}</td>
+                        <td>{m.distribution.name_as_str()}</td>
+                        <td>{m.items}</td>
+                        <td>{m.steps}</td>
+                        <td>{m.counters.accesses}</td>
+                        <td>{m.counters.updates}</td>
+                        <td>{m.counters.comparisons}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <table class="table table-sm table-hover col-11 mt-3">
+            <thead>
+                <tr>
+                    {Column::ALL
+                        .into_iter()
+                        .map(|column| view! {
+                            <th role="button"
+                                on:click=move |_| order.update(|(by, ascending)| {
+                                    *ascending = *by != column || !*ascending;
+                                    *by = column;
+                                })>
+                                {column.name_as_str()}
+                                {move || match order.get() {
+                                    (by, true) if by == column => " ▲",
+                                    (by, false) if by == column => " ▼",
+                                    _ => "",
+                                }}
+                            </th>
+                        })
+                        .collect_view()}
+                </tr>
+            </thead>
+            <tbody>{rows}</tbody>
+        </table>
+    }
+}
@@ -0,0 +1,751 @@
+use leptos::*;
+use leptos_sort::visual_sort::bins::{Bin, Bins};
+use leptos_sort::visual_sort::image::{self, Image};
+use leptos_sort::visual_sort::recursion::CallTree;
+use leptos_sort::visual_sort::{AuxBuffer, Color, Frame, HeapView, Scale};
+use std::cell::RefCell;
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, ImageData};
+
+/// How the data is laid out on the canvas.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Render {
+    Bars,
+    Scatter,
+    Hue,
+    Disparity,
+    Spiral,
+}
+
+impl Render {
+    pub const ALL: [Render; 5] = [
+        Self::Bars,
+        Self::Scatter,
+        Self::Hue,
+        Self::Disparity,
+        Self::Spiral,
+    ];
+
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Bars => "Bars",
+            Self::Scatter => "Scatter",
+            Self::Hue => "Hue strip",
+            Self::Disparity => "Disparity circle",
+            Self::Spiral => "Spiral",
+        }
+    }
+
+    pub fn key_as_str(&self) -> &'static str {
+        match self {
+            Self::Bars => "bars",
+            Self::Scatter => "scatter",
+            Self::Hue => "hue",
+            Self::Disparity => "disparity",
+            Self::Spiral => "spiral",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.key_as_str() == key)
+    }
+}
+
+/// Columns of a canvas with more items than pixels across, as last drawn.
+struct Binned {
+    /// Gathered on the first draw, once there is data.
+    bins: Option<Bins>,
+    colors: Vec<Color>,
+    /// Columns of the highlights last drawn, to recolor once they move on.
+    highlighted: Vec<usize>,
+}
+
+/// Draws data in one of the render layouts, bars by default.
+///
+/// With more items than pixels across, items are binned: bars, scatter and hue are drawn
+/// as pixel columns, redrawing only the columns that changed, and the circles as a dot
+/// for each column.
+pub struct Canvas {
+    canvas_h: f64,
+    canvas_w: f64,
+    ctx2d: CanvasRenderingContext2d,
+    spacing: f64,
+    col_width: f64,
+    scale: Scale,
+    render: Render,
+    binned: Option<RefCell<Binned>>,
+    /// Keys of the data in sorted order, gathered on the first disparity draw.
+    sorted: RefCell<Option<Vec<usize>>>,
+}
+
+impl Canvas {
+    /// Largest side of a dot in the point layouts
+    const MAX_DOT: f64 = 8.0;
+
+    pub fn new(
+        canvas_ref: &NodeRef<html::Canvas>,
+        items: usize,
+        scale: Scale,
+        render: Render,
+    ) -> Self {
+        let len = items as f64;
+
+        let (canvas_w, canvas_h, ctx2d) = context(canvas_ref);
+
+        // no spacing if low pixel per item
+        let spacing = if canvas_w / len > 4.0 { 2.0 } else { 0.0 };
+
+        // how wide can one item be for all items to fill the canvas, no spacing front or end
+        let col_width = (canvas_w + spacing - (spacing * len)) / len;
+
+        let binned = (items > canvas_w as usize).then(|| {
+            RefCell::new(Binned {
+                bins: None,
+                colors: Vec::new(),
+                highlighted: Vec::new(),
+            })
+        });
+
+        Self {
+            canvas_h,
+            canvas_w,
+            ctx2d,
+            spacing,
+            col_width,
+            scale,
+            render,
+            binned,
+            sorted: RefCell::new(None),
+        }
+    }
+
+    pub fn set_render(&mut self, render: Render) {
+        self.render = render;
+        self.invalidate();
+    }
+
+    pub fn clear(&self) {
+        self.ctx2d
+            .clear_rect(0.0, 0.0, self.canvas_w, self.canvas_h);
+        self.invalidate();
+    }
+
+    /// Draw the data, given the indices changed since the last draw when they are known.
+    ///
+    /// Highlights are the indices color may give other than their resting color, see
+    /// [`VisualSort::highlights`](leptos_sort::visual_sort::VisualSort::highlights).
+    pub fn draw<F>(&self, data: &[usize], changed: Option<&[usize]>, highlights: &[usize], color: F)
+    where
+        F: Fn(usize) -> Color,
+    {
+        if let Some(binned) = &self.binned {
+            let changes = Changes {
+                changed,
+                highlights,
+            };
+            self.binned(&mut binned.borrow_mut(), data, changes, color);
+            return;
+        }
+        self.clear();
+        match self.render {
+            Render::Bars => self.bars(data, color),
+            Render::Scatter => self.scatter(data, color),
+            Render::Hue => self.hue(data, color),
+            Render::Disparity => {
+                let len = data.len() as f64;
+                let sorted = self.sorted_keys(data);
+                let distances = disparities(data, self.scale, &sorted);
+                self.circle(data.len(), color, |i| 1.0 - distances[i] as f64 / len);
+            }
+            Render::Spiral => {
+                self.circle(data.len(), color, |i| self.scale.fraction(data[i]));
+            }
+        }
+    }
+
+    /// Keys of the data sorted, kept from the first call since sorting only moves them
+    fn sorted_keys(&self, data: &[usize]) -> std::cell::Ref<'_, Vec<usize>> {
+        if self.sorted.borrow().is_none() {
+            let mut sorted: Vec<usize> = data.iter().map(|&v| self.scale.key(v)).collect();
+            sorted.sort_unstable();
+            *self.sorted.borrow_mut() = Some(sorted);
+        }
+        std::cell::Ref::map(self.sorted.borrow(), |sorted| {
+            sorted.as_ref().expect("sorted keys to be gathered")
+        })
+    }
+
+    /// Have every binned column drawn again on the next draw
+    fn invalidate(&self) {
+        if let Some(binned) = &self.binned {
+            if let Some(bins) = binned.borrow_mut().bins.as_mut() {
+                bins.mark_all();
+            }
+        }
+    }
+
+    /// Put the columns that changed into the canvas as image data, one pixel wide each,
+    /// or draw the circles with a dot for each column
+    fn binned(
+        &self,
+        binned: &mut Binned,
+        data: &[usize],
+        changes: Changes,
+        color: impl Fn(usize) -> Color,
+    ) {
+        let fresh = binned.bins.is_none();
+        let bins = binned
+            .bins
+            .get_or_insert_with(|| Bins::new(data, self.canvas_w as usize));
+        let column = |i: &usize| (*i < data.len()).then(|| bins.column(*i));
+        let highlighted: Vec<usize> = changes.highlights.iter().filter_map(column).collect();
+
+        // only columns whose values or highlights may have changed are colored again
+        let recolor: Vec<usize> = match changes.changed {
+            Some(changed) if !fresh => {
+                let mut columns: Vec<usize> = changed
+                    .iter()
+                    .filter_map(column)
+                    .chain(highlighted.iter().copied())
+                    .chain(binned.highlighted.drain(..))
+                    .collect();
+                columns.sort_unstable();
+                columns.dedup();
+                bins.update(data, changed.iter().copied());
+                columns
+            }
+            _ => {
+                bins.update_all(data);
+                (0..bins.len()).collect()
+            }
+        };
+        binned.highlighted = highlighted;
+
+        // a column shows the strongest highlight among its items, otherwise its first item's color
+        binned.colors.resize(bins.len(), Color::Red);
+        for column in recolor {
+            let range = bins.range(column);
+            let mut column_color = color(range.start);
+            for i in range {
+                match color(i) {
+                    Color::Green => {
+                        column_color = Color::Green;
+                        break;
+                    }
+                    Color::Light => column_color = Color::Light,
+                    _ => {}
+                }
+            }
+            if binned.colors[column] != column_color {
+                binned.colors[column] = column_color;
+                bins.mark(column);
+            }
+        }
+
+        let dirty = bins.take_dirty();
+        if let Render::Disparity | Render::Spiral = self.render {
+            // no more dots than pixels across, so the whole circle is drawn again
+            self.ctx2d
+                .clear_rect(0.0, 0.0, self.canvas_w, self.canvas_h);
+            let (bins, colors) = (&*bins, |column: usize| binned.colors[column]);
+            if self.render == Render::Disparity {
+                let sorted = self.sorted_keys(data);
+                let len = data.len() as f64;
+                self.circle(bins.len(), colors, |column| {
+                    // the middle of the column against where its average belongs
+                    let range = bins.range(column);
+                    let key = self.scale.key(bins.get(column).avg());
+                    let distance = disparity((range.start + range.end) / 2, key, &sorted);
+                    1.0 - distance as f64 / len
+                });
+            } else {
+                self.circle(bins.len(), colors, |column| {
+                    self.scale.fraction(bins.get(column).avg())
+                });
+            }
+            return;
+        }
+
+        let height = self.canvas_h as usize;
+        for span in dirty.chunk_by(|a, b| a + 1 == *b) {
+            let width = span.len();
+            let mut pixels = vec![0; width * height * 4];
+            for (x, &column) in span.iter().enumerate() {
+                let rgba = self.column(bins.get(column), binned.colors[column], height);
+                for (y, pixel) in rgba.enumerate() {
+                    let at = (y * width + x) * 4;
+                    pixels[at..at + 4].copy_from_slice(&pixel);
+                }
+            }
+            let image = ImageData::new_with_u8_clamped_array_and_sh(
+                Clamped(&pixels),
+                width as u32,
+                height as u32,
+            )
+            .expect("image data to fit the columns");
+            let _ = self.ctx2d.put_image_data(&image, span[0] as f64, 0.0);
+        }
+    }
+
+    /// Pixels of a binned column from the top, bars fading from the smallest past the average
+    /// to the largest item, a dot spanning them, or the hue of the average
+    fn column(&self, bin: Bin, color: Color, height: usize) -> impl Iterator<Item = [u8; 4]> {
+        let level = |value: usize| (self.scale.fraction(value) * height as f64).round() as usize;
+        let (min, avg, max) = (level(bin.min), level(bin.avg()), level(bin.max));
+        let [r, g, b] = match (self.render, color) {
+            (Render::Hue, Color::Green | Color::Light) | (Render::Bars | Render::Scatter, _) => {
+                color.rgb()
+            }
+            _ => image::hue(self.scale.fraction(bin.avg())),
+        };
+        let render = self.render;
+        (0..height).map(move |y| {
+            // height above the bottom of the canvas, the bottom row being 1
+            let h = height - y;
+            let alpha = match render {
+                Render::Bars if h <= min => 255,
+                Render::Bars if h <= avg => 170,
+                Render::Bars if h <= max => 80,
+                Render::Scatter if min.max(1) <= h && h <= max => 255,
+                Render::Hue => 255,
+                _ => 0,
+            };
+            [r, g, b, alpha]
+        })
+    }
+
+    fn bars(&self, data: &[usize], color: impl Fn(usize) -> Color) {
+        for (i, num) in data.iter().enumerate() {
+            let y = self.scale.fraction(*num) * self.canvas_h;
+            // draw item inside canvas, with width and spacing, no spacing front or end
+            let x = i as f64 * (self.col_width + self.spacing);
+            self.ctx2d.set_fill_style(&JsValue::from(color(i).as_str()));
+            self.ctx2d.begin_path();
+            self.ctx2d.rect(x, self.canvas_h - y, self.col_width, y);
+            self.ctx2d.close_path();
+            self.ctx2d.fill();
+        }
+    }
+
+    /// A dot for each item where the top of its bar would be
+    fn scatter(&self, data: &[usize], color: impl Fn(usize) -> Color) {
+        let dot = (self.col_width + self.spacing).clamp(2.0, Self::MAX_DOT);
+        for (i, num) in data.iter().enumerate() {
+            let x = i as f64 * (self.col_width + self.spacing) + self.col_width / 2.0;
+            let y = self.canvas_h - self.scale.fraction(*num) * (self.canvas_h - dot);
+            self.ctx2d.set_fill_style(&JsValue::from(color(i).as_str()));
+            self.ctx2d.fill_rect(x - dot / 2.0, y - dot, dot, dot);
+        }
+    }
+
+    /// Full height columns colored by value, items being worked on in their highlight
+    fn hue(&self, data: &[usize], color: impl Fn(usize) -> Color) {
+        // columns touch, so the strip reads as one gradient once sorted
+        let width = self.canvas_w / data.len().max(1) as f64;
+        for (i, num) in data.iter().enumerate() {
+            let fill = match color(i) {
+                highlight @ (Color::Green | Color::Light) => highlight.as_str().to_string(),
+                _ => {
+                    let [r, g, b] = image::hue(self.scale.fraction(*num));
+                    format!("rgb({r}, {g}, {b})")
+                }
+            };
+            self.ctx2d.set_fill_style(&JsValue::from(fill));
+            self.ctx2d
+                .fill_rect(i as f64 * width, 0.0, width.ceil(), self.canvas_h);
+        }
+    }
+
+    /// A dot for each item, at an angle by its index and a share of the radius given by reach
+    fn circle(&self, len: usize, color: impl Fn(usize) -> Color, reach: impl Fn(usize) -> f64) {
+        let (cx, cy) = (self.canvas_w / 2.0, self.canvas_h / 2.0);
+        let outer = cx.min(cy) - Self::MAX_DOT;
+        let dot = (std::f64::consts::TAU * outer / len.max(1) as f64).clamp(2.0, Self::MAX_DOT);
+        for i in 0..len {
+            // first item at the top, going clockwise
+            let angle = std::f64::consts::TAU * i as f64 / len as f64 - std::f64::consts::FRAC_PI_2;
+            let radius = reach(i) * outer;
+            let (x, y) = (cx + radius * angle.cos(), cy + radius * angle.sin());
+            self.ctx2d.set_fill_style(&JsValue::from(color(i).as_str()));
+            self.ctx2d.fill_rect(x - dot / 2.0, y - dot / 2.0, dot, dot);
+        }
+    }
+}
+
+/// What changed since the last draw, for canvases that redraw only that
+struct Changes<'a> {
+    /// Indices whose values changed, None when they may have changed anywhere.
+    changed: Option<&'a [usize]>,
+    highlights: &'a [usize],
+}
+
+/// How far each item is from where it belongs once sorted, given the keys sorted
+fn disparities(data: &[usize], scale: Scale, sorted: &[usize]) -> Vec<usize> {
+    data.iter()
+        .enumerate()
+        .map(|(i, &v)| disparity(i, scale.key(v), sorted))
+        .collect()
+}
+
+/// How far index i is from where its key belongs once sorted, zero anywhere among equal keys
+fn disparity(i: usize, key: usize, sorted: &[usize]) -> usize {
+    let first = sorted.partition_point(|&k| k < key);
+    let last = sorted.partition_point(|&k| k <= key).max(1) - 1;
+    first.saturating_sub(i) + i.saturating_sub(last)
+}
+
+/// Draws auxiliary buffers as bars, each buffer in a lane of its own.
+pub struct Lanes {
+    canvas_h: f64,
+    canvas_w: f64,
+    ctx2d: CanvasRenderingContext2d,
+    scale: Scale,
+}
+
+impl Lanes {
+    pub fn new(canvas_ref: &NodeRef<html::Canvas>, scale: Scale) -> Self {
+        let (canvas_w, canvas_h, ctx2d) = context(canvas_ref);
+        Self {
+            canvas_h,
+            canvas_w,
+            ctx2d,
+            scale,
+        }
+    }
+
+    pub fn clear(&self) {
+        self.ctx2d
+            .clear_rect(0.0, 0.0, self.canvas_w, self.canvas_h);
+    }
+
+    pub fn draw(&self, buffers: &[AuxBuffer]) {
+        self.clear();
+        let lane_h = self.canvas_h / buffers.len().max(1) as f64;
+        for (lane, buffer) in buffers.iter().enumerate() {
+            let bottom = lane_h * (lane + 1) as f64;
+            // counts are drawn against the largest count, values as in the data
+            let largest = buffer.values.iter().copied().max().unwrap_or(0).max(1) as f64;
+            // long buffers are drawn a pixel column at a time, showing the largest in each
+            let bins = Bins::new(&buffer.values, self.canvas_w as usize);
+            let col_width = self.canvas_w / bins.len() as f64;
+            for i in 0..bins.len().min(buffer.values.len()) {
+                let value = bins.get(i).max;
+                let range = bins.range(i);
+                let fraction = if buffer.aux.holds_counts() {
+                    value as f64 / largest
+                } else {
+                    self.scale.fraction(value)
+                };
+                let color = match buffer.cursor {
+                    Some(cursor) if range.contains(&cursor) => Color::Green,
+                    Some(cursor) if range.end <= cursor && !buffer.aux.holds_counts() => {
+                        Color::Light
+                    }
+                    _ => Color::Red,
+                };
+                // leave a little room between lanes
+                let y = fraction * (lane_h - 4.0);
+                self.ctx2d.set_fill_style(&JsValue::from(color.as_str()));
+                self.ctx2d
+                    .fill_rect(i as f64 * col_width, bottom - y, col_width, y);
+            }
+            self.ctx2d.set_fill_style(&JsValue::from("#adb5bd"));
+            self.ctx2d.set_font("12px sans-serif");
+            let _ = self
+                .ctx2d
+                .fill_text(buffer.aux.name_as_str(), 4.0, bottom - lane_h + 14.0);
+        }
+    }
+}
+
+/// Draws the heap region of the data as a binary tree, with the data after it as bars below.
+///
+/// Only the levels with room for their nodes are drawn, the deeper ones summed up below.
+pub struct Tree {
+    canvas_h: f64,
+    canvas_w: f64,
+    ctx2d: CanvasRenderingContext2d,
+    scale: Scale,
+}
+
+impl Tree {
+    /// Share of the height given to the tree, the rest shows the data after the heap
+    const TREE_SHARE: f64 = 0.8;
+    /// Smallest node radius drawn, deeper levels would be smaller
+    const MIN_RADIUS: f64 = 2.0;
+
+    pub fn new(canvas_ref: &NodeRef<html::Canvas>, scale: Scale) -> Self {
+        let (canvas_w, canvas_h, ctx2d) = context(canvas_ref);
+        Self {
+            canvas_h,
+            canvas_w,
+            ctx2d,
+            scale,
+        }
+    }
+
+    pub fn clear(&self) {
+        self.ctx2d
+            .clear_rect(0.0, 0.0, self.canvas_w, self.canvas_h);
+    }
+
+    /// Center of node i, each level of the tree spread over the full width
+    fn position(&self, i: usize, rows: u32) -> (f64, f64) {
+        let level = (i + 1).ilog2();
+        let first = (1 << level) - 1;
+        let width = self.canvas_w / (1 << level) as f64;
+        let level_h = self.canvas_h * Self::TREE_SHARE / rows as f64;
+        let x = (i - first) as f64 * width + width / 2.0;
+        let y = level as f64 * level_h + level_h / 2.0;
+        (x, y)
+    }
+
+    /// Draw the heap, marking the sifted node and the edges swapped along by the latest step.
+    pub fn draw(&self, data: &[usize], heap: HeapView, swaps: &[(usize, usize)]) {
+        self.clear();
+        let levels = (heap.len + 1).next_power_of_two().ilog2().max(1);
+        // levels whose bottom nodes are at least the smallest radius across
+        let widest = (self.canvas_w / (2.0 * (Self::MIN_RADIUS + 1.0))).max(1.0) as usize;
+        let shown = levels.min(widest.ilog2() + 1);
+        let nodes = heap.len.min((1 << shown) - 1);
+        // a row below the tree for the summary of the levels left out
+        let rows = shown + u32::from(shown < levels);
+        let level_h = self.canvas_h * Self::TREE_SHARE / rows as f64;
+        let bottom_width = self.canvas_w / (1 << (shown - 1)) as f64;
+        let radius = (bottom_width.min(level_h) / 2.0 - 1.0).clamp(0.5, 14.0);
+
+        // edges to each parent, swapped ones stand out
+        for i in 1..nodes {
+            let parent = (i - 1) / 2;
+            let swapped = swaps.contains(&(parent, i)) || swaps.contains(&(i, parent));
+            let (color, width) = if swapped {
+                (Color::Green.as_str(), 3.0)
+            } else {
+                ("#495057", 1.0)
+            };
+            let (x, y) = self.position(i, rows);
+            let (px, py) = self.position(parent, rows);
+            self.ctx2d.set_stroke_style(&JsValue::from(color));
+            self.ctx2d.set_line_width(width);
+            self.ctx2d.begin_path();
+            self.ctx2d.move_to(px, py);
+            self.ctx2d.line_to(x, y);
+            self.ctx2d.stroke();
+        }
+
+        // a node sifted below the levels shown is marked at its ancestor on the last one
+        let sifting = heap.sifting.map(|mut i| {
+            while i >= nodes && i > 0 {
+                i = (i - 1) / 2;
+            }
+            i
+        });
+
+        // nodes, labeled with their value when there is room
+        self.ctx2d.set_font("11px sans-serif");
+        self.ctx2d.set_text_align("center");
+        self.ctx2d.set_text_baseline("middle");
+        for (i, value) in data[..nodes].iter().enumerate() {
+            let color = if sifting == Some(i) {
+                Color::Light
+            } else {
+                Color::Red
+            };
+            let (x, y) = self.position(i, rows);
+            self.ctx2d.set_fill_style(&JsValue::from(color.as_str()));
+            self.ctx2d.begin_path();
+            let _ = self.ctx2d.arc(x, y, radius, 0.0, std::f64::consts::TAU);
+            self.ctx2d.fill();
+            if radius >= 10.0 {
+                self.ctx2d.set_fill_style(&JsValue::from("#000000"));
+                let _ = self
+                    .ctx2d
+                    .fill_text(&self.scale.key(*value).to_string(), x, y);
+            }
+        }
+        if nodes < heap.len {
+            let summary = format!(
+                "{} more nodes in {} deeper levels",
+                heap.len - nodes,
+                levels - shown
+            );
+            self.ctx2d.set_fill_style(&JsValue::from("#adb5bd"));
+            let y = shown as f64 * level_h + level_h / 2.0;
+            let _ = self.ctx2d.fill_text(&summary, self.canvas_w / 2.0, y);
+        }
+
+        // the rest of the data, input waiting to be pushed or the sorted suffix
+        let top = self.canvas_h * Self::TREE_SHARE;
+        let rest_h = self.canvas_h - top;
+        let color = if heap.building {
+            Color::Light
+        } else {
+            Color::Red
+        };
+        self.ctx2d.set_fill_style(&JsValue::from(color.as_str()));
+        // no more bars than pixels across, each as tall as the largest item it covers
+        let columns = (self.canvas_w as usize).clamp(1, data.len().max(1));
+        let col_width = self.canvas_w / columns as f64;
+        let mut tallest: Option<(usize, usize)> = None;
+        for (i, &value) in data.iter().enumerate().skip(heap.len) {
+            let column = i * columns / data.len();
+            tallest = match tallest {
+                Some((at, max)) if at == column => Some((at, max.max(value))),
+                _ => {
+                    if let Some((at, max)) = tallest {
+                        self.rest_bar(at, col_width, max, rest_h);
+                    }
+                    Some((column, value))
+                }
+            };
+        }
+        if let Some((at, max)) = tallest {
+            self.rest_bar(at, col_width, max, rest_h);
+        }
+    }
+
+    /// A bar of the data after the heap in the column at, as tall as value
+    fn rest_bar(&self, at: usize, col_width: f64, value: usize, rest_h: f64) {
+        let y = self.scale.fraction(value) * rest_h;
+        self.ctx2d
+            .fill_rect(at as f64 * col_width, self.canvas_h - y, col_width, y);
+    }
+}
+
+/// Draws the calls of a recursive sort as nested ranges, one row per depth.
+///
+/// Calls are drawn as they are first seen and again as they finish, leaving the rest of
+/// the canvas as it was.
+pub struct Calls {
+    canvas_h: f64,
+    canvas_w: f64,
+    ctx2d: CanvasRenderingContext2d,
+    items: usize,
+    drawn: RefCell<DrawnCalls>,
+}
+
+/// Calls on the canvas, to know which to draw again
+#[derive(Default)]
+struct DrawnCalls {
+    /// Calls drawn so far, the first ones of the tree.
+    len: usize,
+    /// Rows the calls were laid out in, all are drawn again when there are more.
+    rows: usize,
+    /// Calls drawn before they finished.
+    open: Vec<usize>,
+    current: Option<usize>,
+}
+
+impl Calls {
+    /// Tallest a row of calls is drawn
+    const MAX_ROW_H: f64 = 24.0;
+
+    pub fn new(canvas_ref: &NodeRef<html::Canvas>, items: usize) -> Self {
+        let (canvas_w, canvas_h, ctx2d) = context(canvas_ref);
+        Self {
+            canvas_h,
+            canvas_w,
+            ctx2d,
+            items,
+            drawn: RefCell::new(DrawnCalls::default()),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.ctx2d
+            .clear_rect(0.0, 0.0, self.canvas_w, self.canvas_h);
+        *self.drawn.borrow_mut() = DrawnCalls::default();
+    }
+
+    /// Draw the calls new or finished since the last draw, greying out finished ones and
+    /// marking the current one.
+    pub fn draw(&self, call_tree: &CallTree, current: Option<&Frame>) {
+        let rows = call_tree.max_depth() + 1;
+        if rows != self.drawn.borrow().rows {
+            self.clear();
+            self.drawn.borrow_mut().rows = rows;
+        }
+        let drawn = &mut *self.drawn.borrow_mut();
+        let calls = call_tree.calls();
+
+        // calls that finished or stopped being current since, then the new ones
+        let mut redraw: Vec<usize> = drawn.current.take().into_iter().collect();
+        drawn.open.retain(|&i| {
+            let finished = calls[i].finished;
+            if finished {
+                redraw.push(i);
+            }
+            !finished
+        });
+        redraw.extend(drawn.len..calls.len());
+        drawn
+            .open
+            .extend((drawn.len..calls.len()).filter(|&i| !calls[i].finished));
+        drawn.len = calls.len();
+        drawn.current = current.and_then(|frame| {
+            let open = drawn.open.iter().rev();
+            open.copied().find(|&i| calls[i].frame == *frame)
+        });
+        redraw.extend(drawn.current);
+
+        let row_h = (self.canvas_h / rows as f64).min(Self::MAX_ROW_H);
+        let col_width = self.canvas_w / self.items.max(1) as f64;
+        for i in redraw {
+            let call = calls[i];
+            let frame = call.frame;
+            let color = if drawn.current == Some(i) {
+                Color::Green.as_str()
+            } else if call.finished {
+                "#495057"
+            } else {
+                Color::Red.as_str()
+            };
+            // a pixel apart so neighbouring calls stay apart
+            let x = frame.start as f64 * col_width;
+            let width = ((frame.end - frame.start) as f64 * col_width - 1.0).max(1.0);
+            let y = frame.depth as f64 * row_h;
+            self.ctx2d.set_fill_style(&JsValue::from(color));
+            self.ctx2d.fill_rect(x, y, width, (row_h - 1.0).max(1.0));
+        }
+    }
+}
+
+/// Put an image on the canvas pixel for pixel, returning it as a PNG data URL to download.
+///
+/// The canvas takes the size of the image, how large it is shown is left to its style.
+pub fn draw_image(canvas_ref: &NodeRef<html::Canvas>, image: &Image) -> String {
+    let canvas = canvas_ref.get_untracked().expect("canvas should exist");
+    canvas.set_width(image.width as u32);
+    canvas.set_height(image.height as u32);
+    let ctx2d = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<CanvasRenderingContext2d>()
+        .expect("canvas to have 2d context");
+    let data = ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(&image.rgba()),
+        image.width as u32,
+        image.height as u32,
+    )
+    .expect("image data to fit the image");
+    let _ = ctx2d.put_image_data(&data, 0.0, 0.0);
+    canvas.to_data_url().expect("canvas to encode as png")
+}
+
+/// Size the canvas to how it is shown, returning width, height and its 2d context
+fn context(canvas_ref: &NodeRef<html::Canvas>) -> (f64, f64, CanvasRenderingContext2d) {
+    let canvas = canvas_ref.get_untracked().expect("canvas should exist");
+    let canvas_w = canvas.client_width() as f64;
+    let canvas_h = canvas.client_height() as f64;
+    canvas.set_width(canvas_w as u32);
+    canvas.set_height(canvas_h as u32);
+
+    let ctx2d = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<CanvasRenderingContext2d>()
+        .expect("canvas to have 2d context");
+    (canvas_w, canvas_h, ctx2d)
+}
@@ -1,15 +1,42 @@
-mod visual_sort;
+mod audio;
+mod benchmark;
+mod canvas;
+mod race;
+mod run;
 
-use leptos::html::Canvas;
+use audio::{wav_url, Audio, Sound};
+use benchmark::{BenchmarkPage, FRAME_MS, STEPS_PER_CHUNK};
+use canvas::{draw_image, Calls, Canvas, Lanes, Render, Tree};
 use leptos::*;
 use leptos_router::*;
+use leptos_sort::visual_sort::image::Snapshots;
+use leptos_sort::visual_sort::input::{self, Distribution};
+use leptos_sort::visual_sort::pitch::{Mapping, Pitch, Tuning};
+use leptos_sort::visual_sort::sound::Waveform;
+use leptos_sort::visual_sort::soundtrack::{Rendering, Synth};
+use leptos_sort::visual_sort::stability;
+use leptos_sort::visual_sort::{Counters, Frame, Sort, SortBase};
+use race::Race;
+use run::{steps_per_tick, Run};
 use std::cell::RefCell;
 use std::rc::Rc;
-use visual_sort::{Sort, SortParams, VisualSort};
 use wasm_bindgen::{prelude::Closure, JsCast};
 
 type Callback = Rc<RefCell<Closure<dyn FnMut(f64)>>>;
 
+/// Most items of generated and custom input alike
+const MAX_ITEMS: usize = 1_000_000;
+/// Positions of the items slider per tenfold of items
+const SLIDER_DECADE: f64 = 100.0;
+/// Most snapshots in a sort history image, one row of pixels each
+const HISTORY_ROWS: usize = 600;
+/// Width of a sort history image, items are widened or binned to fit
+const HISTORY_WIDTH: usize = 1200;
+/// Sample rate of a soundtrack, plenty for the pitches played and half the size of CD audio
+const SOUNDTRACK_RATE: u32 = 22_050;
+/// Longest soundtrack rendered, longer runs are played faster to fit
+const SOUNDTRACK_SECONDS: f64 = 180.0;
+
 fn main() {
     _ = console_log::init_with_level(log::Level::Debug);
     console_error_panic_hook::set_once();
@@ -22,7 +49,13 @@ fn App() -> impl IntoView {
     let update_ms = create_rw_signal(25);
     let play = create_rw_signal(false);
     let items = create_rw_signal(50);
-    let volume = create_rw_signal(0.1);
+    let seed = create_rw_signal(input::random_seed());
+    let distribution = create_rw_signal(Distribution::Shuffled);
+    let custom = create_rw_signal(None::<Vec<usize>>);
+    let stability = create_rw_signal(false);
+    let sound = Sound::new();
+    let audio = store_value(Audio::new(sound));
+    let render = create_rw_signal(Render::Bars);
     view! {
         <Router>
             <div class="d-flex flex-row vh-100">
@@ -34,35 +67,43 @@ fn App() -> impl IntoView {
                     />
                     <Route
                         path=Sort::Bubble.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Bubble play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Bubble play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Counting.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Counting play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Counting play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Heap.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Heap play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Heap play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Insertion.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Insertion play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Insertion play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Merge.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Merge play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Merge play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Radix.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Radix play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Radix play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Quick.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Quick play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Quick play update_ms items seed distribution custom stability sound audio render/> }
                     />
                     <Route
                         path=Sort::Selection.route_as_str()
-                        view=move || view! { <DisplaySort sort=Sort::Selection play update_ms items volume/> }
+                        view=move || view! { <DisplaySort sort=Sort::Selection play update_ms items seed distribution custom stability sound audio render/> }
+                    />
+                    <Route
+                        path=race::ROUTE
+                        view=move || view! { <Race play update_ms items seed distribution custom render/> }
+                    />
+                    <Route
+                        path=benchmark::ROUTE
+                        view=move || view! { <BenchmarkPage seed/> }
                     />
                     <Route
                         path="/*"
@@ -101,6 +142,8 @@ fn Sidebar() -> impl IntoView {
                 <SidebarSortItem sort=Sort::Radix icon="flower2"/>
                 <SidebarSortItem sort=Sort::Quick icon="vr"/>
                 <SidebarSortItem sort=Sort::Selection icon="arrows-collapse-vertical"/>
+                <SidebarPageItem route=race::ROUTE name="Race" icon="flag"/>
+                <SidebarPageItem route=benchmark::ROUTE name="Benchmark" icon="speedometer2"/>
             </ul>
             <hr/>
             <div class="text-secondary ps-3">
@@ -128,6 +171,20 @@ fn SidebarSortItem(sort: Sort, icon: &'static str) -> impl IntoView {
     }
 }
 
+#[component]
+fn SidebarPageItem(route: &'static str, name: &'static str, icon: &'static str) -> impl IntoView {
+    let location = use_location();
+    view! {
+        <li>
+            <a href=route class="nav-link text-white"
+                class:bg-danger=move || location.pathname.get() == route >
+                <i class={ format!("bi bi-{icon} me-2") }></i>
+                { name }
+            </a>
+        </li>
+    }
+}
+
 #[component]
 fn Home() -> impl IntoView {
     view! {
@@ -147,66 +204,283 @@ fn DisplaySort(
     play: RwSignal<bool>,
     update_ms: RwSignal<usize>,
     items: RwSignal<usize>,
-    volume: RwSignal<f32>,
+    seed: RwSignal<u64>,
+    distribution: RwSignal<Distribution>,
+    custom: RwSignal<Option<Vec<usize>>>,
+    stability: RwSignal<bool>,
+    sound: Sound,
+    audio: StoredValue<Audio>,
+    render: RwSignal<Render>,
 ) -> impl IntoView {
-    let mut sorter_holder: Option<Box<dyn VisualSort>> = None;
+    let run = store_value(None::<Run>);
     let mut prev_update = 0.0;
 
-    let array_access = create_rw_signal(0);
-    let array_swap = create_rw_signal(0);
-    let array_cmp = create_rw_signal(0);
+    let counters = create_rw_signal(Counters::default());
+    let step = create_rw_signal(0);
+    let first_step = create_rw_signal(0);
+    let last_step = create_rw_signal(0);
+    let stable = create_rw_signal(None::<bool>);
+    let line = create_rw_signal(None::<usize>);
+    let peak_aux = create_rw_signal(0);
+    let tree_view = create_rw_signal(false);
+    let frames = create_rw_signal(Vec::<Frame>::new());
+    let max_depth = create_rw_signal(0);
+    let history_url = create_rw_signal(None::<String>);
+    let soundtrack_url = create_rw_signal(None::<String>);
+    let export = store_value(None::<Export>);
+    let exporting = create_rw_signal(false);
+    let export_progress = create_rw_signal(None::<f64>);
 
     let sort_name = sort.name_as_str();
-    let canvas_ref = create_node_ref::<Canvas>();
+    let canvas_ref = create_node_ref::<html::Canvas>();
+    let lanes_ref = create_node_ref::<html::Canvas>();
+    let tree_ref = create_node_ref::<html::Canvas>();
+    let calls_ref = create_node_ref::<html::Canvas>();
+    let history_ref = create_node_ref::<html::Canvas>();
     let window = web_sys::window().unwrap();
     let document = leptos::document();
     let location = use_location();
     let start_loc = location.pathname.get_untracked();
+    let export_loc = (location.clone(), start_loc.clone());
 
-    let draw: Callback = Rc::new(RefCell::new(Closure::new(move |_| ())));
-    let draw_clone = draw.clone();
+    // data of a new run, tagged in stability mode
+    let new_base = move || {
+        let data = input_data(items, seed, distribution, custom);
+        match stability.get_untracked() {
+            // custom input already has the duplicates it should have
+            true if custom.with_untracked(Option::is_some) => {
+                SortBase::tagged(stability::custom_keys(&data))
+            }
+            true => SortBase::tagged(stability::keys(&data)),
+            false => SortBase::new(data),
+        }
+    };
 
-    *draw.borrow_mut() = Closure::new(move |prev_end_time| {
-        if prev_update == 0.0 {
-            prev_update = prev_end_time;
+    // continue a paused run, or start a new one if there is none to continue
+    let prepare = move || {
+        let paused = run.with_value(|run| run.as_ref().is_some_and(|run| !run.finished()));
+        audio.with_value(Audio::resume);
+        if paused {
+            return;
+        }
+        counters.set(Counters::default());
+        stable.set(None);
+        line.set(None);
+        let base = new_base();
+        let (items, scale) = (base.data().len(), base.scale());
+        let canvas = Canvas::new(&canvas_ref, items, scale, render.get_untracked());
+        let sorter = sort.init(base);
+        let mut new_run = Run::new(sorter, canvas, audio);
+        if !sort.in_place() {
+            new_run.lanes = Some(Lanes::new(&lanes_ref, scale));
+        }
+        if tree_view.get_untracked() && new_run.sorter.heap().is_some() {
+            new_run.tree = Some(Tree::new(&tree_ref, scale));
+        }
+        if sort.recursive() {
+            new_run.calls = Some(Calls::new(&calls_ref, items));
         }
+        run.set_value(Some(new_run));
+    };
 
-        if sorter_holder.is_none() {
-            array_access.set(0);
-            array_swap.set(0);
-            array_cmp.set(0);
-            let params = SortParams {
-                canvas_ref: &canvas_ref,
-                items: items.get_untracked(),
-                volume,
-                array_access,
-                array_swap,
-                array_cmp,
-            };
-            sorter_holder = Some(sort.init(params));
+    // advance the run and show where it is, true when it has finished
+    let advance = move |ticks: usize| {
+        let mut finished = true;
+        let mut drawn = None;
+        run.update_value(|run| {
+            if let Some(run) = run.as_mut() {
+                run.draw(ticks);
+                let history = &run.history;
+                frames.set(run.sorter.frames());
+                max_depth.set(run.call_tree.max_depth());
+                drawn = Some((
+                    (run.counters, run.peak_aux),
+                    history.first(),
+                    history.position(),
+                    history.len(),
+                ));
+                finished = run.finished();
+                line.set((!run.sorter.done()).then(|| run.sorter.line()));
+                let base = run.sorter.base();
+                if run.sorter.done() && base.scale().is_tagged() {
+                    stable.set(Some(stability::is_stable(base.data(), base.scale())));
+                }
+            }
+        });
+        if let Some(((totals, peak), first, position, last)) = drawn {
+            counters.set(totals);
+            peak_aux.set(peak);
+            first_step.set(first);
+            last_step.set(last);
+            step.set(position);
+        }
+        finished
+    };
+
+    let step_by = move |ticks: usize| {
+        prepare();
+        advance(ticks);
+    };
+
+    // the file stays in memory for as long as it is linked
+    let set_soundtrack_url = move |url: Option<String>| {
+        if let Some(old) = soundtrack_url.get_untracked() {
+            let _ = web_sys::Url::revoke_object_url(&old);
         }
+        soundtrack_url.set(url);
+    };
+
+    let cancel_export = move || {
+        export.set_value(None);
+        exporting.set(false);
+        export_progress.set(None);
+    };
+
+    let reset = move || {
+        play.set(false);
+        audio.with_value(Audio::silence);
+        run.update_value(|run| {
+            if let Some(run) = run.take() {
+                run.clear();
+            }
+        });
+        counters.set(Counters::default());
+        stable.set(None);
+        line.set(None);
+        peak_aux.set(0);
+        frames.set(Vec::new());
+        max_depth.set(0);
+        cancel_export();
+        history_url.set(None);
+        set_soundtrack_url(None);
+        first_step.set(0);
+        last_step.set(0);
+        step.set(0);
+    };
+
+    link_input(sort.route_as_str(), items, seed, distribution, reset);
 
-        if let Some(bubble) = sorter_holder.as_mut() {
-            let now = document.timeline().current_time().unwrap();
-            let delta = now - prev_update;
-            let ticks = delta as usize / update_ms.get_untracked();
-            if ticks > 0 {
-                bubble.draw(ticks);
-                prev_update = now;
+    // the audio outlives the page, leave nothing sounding
+    on_cleanup(move || {
+        audio.with_value(Audio::silence);
+        set_soundtrack_url(None);
+    });
+
+    // work on the export a frame at a time, until it is finished or cancelled
+    let export_work: Callback = Rc::new(RefCell::new(Closure::new(move |_| ())));
+    let export_work_clone = export_work.clone();
+
+    *export_work.borrow_mut() = Closure::new(move |_| {
+        let timeline = leptos::document().timeline();
+        let start = timeline.current_time().unwrap();
+        let mut finished = None;
+        export.update_value(|export| {
+            let Some(work) = export.as_mut() else {
+                return;
+            };
+            while timeline.current_time().unwrap() - start < FRAME_MS {
+                if work.work(STEPS_PER_CHUNK) {
+                    finished = export.take();
+                    return;
+                }
             }
+            export_progress.set(work.progress());
+        });
 
-            if !bubble.done()
-                && play.get_untracked()
-                && start_loc == location.pathname.get_untracked()
+        let (location, start_loc) = &export_loc;
+        match finished {
+            Some(Export::History(snapshots)) => {
+                history_url.set(Some(draw_image(&history_ref, &snapshots.image())));
+                cancel_export();
+            }
+            Some(Export::Soundtrack(rendering)) => {
+                set_soundtrack_url(Some(wav_url(&rendering.soundtrack())));
+                cancel_export();
+            }
+            None if exporting.get_untracked()
+                && *start_loc == location.pathname.get_untracked() =>
             {
-                let _ =
-                    window.request_animation_frame(draw_clone.borrow().as_ref().unchecked_ref());
-            } else {
-                bubble.osc_stop();
-                sorter_holder = None;
-                prev_update = 0.0;
-                play.set(false);
+                let _ = web_sys::window()
+                    .unwrap()
+                    .request_animation_frame(export_work_clone.borrow().as_ref().unchecked_ref());
             }
+            None => cancel_export(),
+        }
+    });
+
+    let start_export = move |work: Export| {
+        export.set_value(Some(work));
+        export_progress.set(None);
+        exporting.set(true);
+        let _ = web_sys::window()
+            .unwrap()
+            .request_animation_frame(export_work.borrow().as_ref().unchecked_ref());
+    };
+
+    // the whole run on the same input, sorted apart from the one shown
+    let show_history = {
+        let start_export = start_export.clone();
+        move || {
+            let snapshots = Snapshots::new(sort, new_base(), HISTORY_ROWS, HISTORY_WIDTH);
+            start_export(Export::History(snapshots));
+        }
+    };
+
+    // the whole run heard as it would play at the current delay, in one file
+    let render_soundtrack = move || {
+        let base = new_base();
+        let per_tick = steps_per_tick(base.data().len());
+        let synth = Synth {
+            sample_rate: SOUNDTRACK_RATE,
+            steps_per_second: 1000.0 / update_ms.get_untracked().max(1) as f64 * per_tick as f64,
+            waveform: sound.waveform.get_untracked(),
+            pitch: sound.pitch.get_untracked(),
+            max_seconds: Some(SOUNDTRACK_SECONDS),
+            ..Synth::default()
+        };
+        start_export(Export::Soundtrack(synth.start(sort, base)));
+    };
+
+    // switching layouts redraws where the run is, paused or not
+    create_effect(move |_| {
+        let render = render.get();
+        run.update_value(|run| {
+            if let Some(run) = run.as_mut() {
+                run.canvas.set_render(render);
+                run.redraw();
+            }
+        });
+    });
+
+    let draw: Callback = Rc::new(RefCell::new(Closure::new(move |_| ())));
+    let draw_clone = draw.clone();
+
+    *draw.borrow_mut() = Closure::new(move |prev_end_time| {
+        if prev_update == 0.0 {
+            prev_update = prev_end_time;
+            prepare();
+        }
+
+        let now = document.timeline().current_time().unwrap();
+        let delta = now - prev_update;
+        let per_tick = run.with_value(|run| {
+            run.as_ref()
+                .map_or(1, |run| steps_per_tick(run.sorter.base().data().len()))
+        });
+        let ticks = delta as usize / update_ms.get_untracked() * per_tick;
+        let finished = if ticks > 0 {
+            prev_update = now;
+            advance(ticks)
+        } else {
+            run.with_value(|run| run.as_ref().is_none_or(Run::finished))
+        };
+
+        if !finished && play.get_untracked() && start_loc == location.pathname.get_untracked() {
+            let _ = window.request_animation_frame(draw_clone.borrow().as_ref().unchecked_ref());
+        } else {
+            audio.with_value(Audio::silence);
+            prev_update = 0.0;
+            play.set(false);
         }
     });
 
@@ -215,36 +489,464 @@ fn DisplaySort(
             <h3 class="p-2">
                 { sort_name.to_string() }
             </h3>
-            <Controls play update_ms items volume draw/>
+            <Controls play update_ms items seed distribution custom stability sound render draw step_by reset/>
+            <CustomInput play custom reset/>
+            <Show when=move || sort == Sort::Heap>
+                <div class="form-check form-switch ms-2">
+                    <input type="checkbox" class="form-check-input" role="switch" id="tree-view"
+                        disabled=move || play.get()
+                        prop:checked=move || tree_view.get()
+                        on:change=move |ev| {
+                            tree_view.set(event_target_checked(&ev));
+                            reset();
+                        }/>
+                    <label class="form-check-label text-muted" for="tree-view">"Tree view"</label>
+                </div>
+            </Show>
             <div class="d-flex justify-content-start h-75 p-2">
-                <canvas class="col-11 border border-1 rounded border-danger" _ref=canvas_ref />
+                <canvas class="col-8 border border-1 rounded border-danger" _ref=canvas_ref />
+                <Pseudocode sort line step last_step/>
+            </div>
+            <Show when=move || tree_view.get()>
+                <div class="d-flex justify-content-start p-2">
+                    <canvas class="col-8 border border-1 rounded border-secondary"
+                        style="height: 320px;" _ref=tree_ref />
+                </div>
+            </Show>
+            <Show when=move || sort.recursive()>
+                <div class="d-flex justify-content-start p-2">
+                    <canvas class="col-8 border border-1 rounded border-secondary"
+                        style="height: 160px;" _ref=calls_ref />
+                    <Stack frames max_depth step last_step/>
+                </div>
+            </Show>
+            <Show when=move || !sort.in_place()>
+                <div class="d-flex justify-content-start p-2">
+                    <canvas class="col-8 border border-1 rounded border-secondary"
+                        style="height: 120px;" _ref=lanes_ref />
+                </div>
+            </Show>
+            <Timeline run play step first_step last_step/>
+            <div class="d-flex align-items-center px-2 mb-2">
+                <button class="btn btn-sm btn-outline-secondary me-2"
+                    title="Every step of the run, one row of pixels each from top to bottom"
+                    disabled=move || play.get() || exporting.get()
+                    on:click=move |_| show_history()>
+                    <i class="bi bi-image me-2"></i>
+                    "History image"
+                </button>
+                <Show when=move || history_url.with(Option::is_some)>
+                    <a class="btn btn-sm btn-outline-secondary"
+                        download=format!("{}-history.png", &sort.route_as_str()[1..])
+                        href=move || history_url.get()>
+                        <i class="bi bi-download me-2"></i>
+                        "Download"
+                    </a>
+                </Show>
+                <button class="btn btn-sm btn-outline-secondary mx-2"
+                    title="The sound of the whole run at the current delay, as a WAV file"
+                    disabled=move || play.get() || exporting.get()
+                    on:click=move |_| render_soundtrack()>
+                    <i class="bi bi-music-note-beamed me-2"></i>
+                    "Soundtrack"
+                </button>
+                <Show when=move || soundtrack_url.with(Option::is_some)>
+                    <a class="btn btn-sm btn-outline-secondary"
+                        download=format!("{}.wav", &sort.route_as_str()[1..])
+                        href=move || soundtrack_url.get()>
+                        <i class="bi bi-download me-2"></i>
+                        "Download WAV"
+                    </a>
+                </Show>
+                <Show when=move || exporting.get()>
+                    <span class="text-muted mx-2">
+                        {move || export_progress.get().map_or_else(
+                            || "Counting steps".to_string(),
+                            |done| format!("{:.0}%", 100.0 * done),
+                        )}
+                    </span>
+                    <button class="btn btn-sm btn-outline-warning"
+                        on:click=move |_| cancel_export()>
+                        <i class="bi bi-x-lg me-2"></i>
+                        "Cancel"
+                    </button>
+                </Show>
             </div>
-            <Details array_access array_swap array_cmp/>
+            <div class="d-flex justify-content-start p-2"
+                class:d-none=move || history_url.with(Option::is_none)>
+                <canvas class="col-8 border border-1 rounded border-secondary"
+                    style="height: 320px; image-rendering: pixelated;" _ref=history_ref />
+            </div>
+            <Details counters/>
+            <Show when=move || !sort.in_place()>
+                <div class="ps-2">"Peak auxiliary memory: "{move || peak_aux.get()}" values"</div>
+            </Show>
+            <Show when=move || stability.get()>
+                <div class="ps-2">
+                    "Stable: "
+                    {move || match (sort.moves_elements(), stable.get()) {
+                        (false, _) => "n/a, values are rebuilt from counts",
+                        (true, None) => "-",
+                        (true, Some(true)) => "yes",
+                        (true, Some(false)) => "no",
+                    }}
+                </div>
+            </Show>
         </div>
     }
 }
 
+/// Work on a file of the whole run, spread over frames so a long run keeps the page alive
+enum Export {
+    History(Snapshots),
+    Soundtrack(Rendering),
+}
+
+impl Export {
+    /// Work up to budget steps, true once finished
+    fn work(&mut self, budget: usize) -> bool {
+        match self {
+            Self::History(snapshots) => snapshots.work(budget),
+            Self::Soundtrack(rendering) => rendering.work(budget),
+        }
+    }
+
+    /// How far the work is, from 0 to 1, or None while the steps are still counted
+    fn progress(&self) -> Option<f64> {
+        match self {
+            Self::History(snapshots) => snapshots.progress(),
+            Self::Soundtrack(rendering) => rendering.progress(),
+        }
+    }
+}
+
+/// Input for a new run: the custom input if there is one, otherwise generated
+fn input_data(
+    items: RwSignal<usize>,
+    seed: RwSignal<u64>,
+    distribution: RwSignal<Distribution>,
+    custom: RwSignal<Option<Vec<usize>>>,
+) -> Vec<usize> {
+    custom.get_untracked().unwrap_or_else(|| {
+        distribution
+            .get_untracked()
+            .generate(items.get_untracked(), seed.get_untracked())
+    })
+}
+
+/// Keep seed, items and distribution in sync with the query of the route
+fn link_input<R>(
+    route: &'static str,
+    items: RwSignal<usize>,
+    seed: RwSignal<u64>,
+    distribution: RwSignal<Distribution>,
+    reset: R,
+) where
+    R: Fn() + Copy + 'static,
+{
+    // take seed, items and distribution from a shared link
+    let query = use_query_map();
+    create_effect(move |_| {
+        query.with(|query| {
+            let linked_seed = query.get("seed").and_then(|v| v.parse().ok());
+            if let Some(linked_seed) = linked_seed.filter(|&v| v != seed.get_untracked()) {
+                seed.set(linked_seed);
+                reset();
+            }
+            let linked_items = query.get("items").and_then(|v| v.parse::<usize>().ok());
+            if let Some(linked_items) = linked_items.filter(|&v| v != items.get_untracked()) {
+                items.set(linked_items.clamp(1, MAX_ITEMS));
+                reset();
+            }
+            let linked_dist = query.get("dist").and_then(|v| Distribution::from_key(v));
+            if let Some(linked_dist) = linked_dist.filter(|&v| v != distribution.get_untracked()) {
+                distribution.set(linked_dist);
+                reset();
+            }
+        });
+    });
+
+    // keep the link in sync with the input in use
+    let navigate = use_navigate();
+    create_effect(move |_| {
+        let (seed, items) = (seed.get().to_string(), items.get().to_string());
+        let dist = distribution.get().key_as_str();
+        let linked = query.with_untracked(|query| {
+            query.get("seed") == Some(&seed)
+                && query.get("items") == Some(&items)
+                && query.get("dist").map(String::as_str) == Some(dist)
+        });
+        if !linked {
+            let path = format!("{route}?seed={seed}&items={items}&dist={dist}");
+            navigate(
+                &path,
+                NavigateOptions {
+                    replace: true,
+                    scroll: false,
+                    ..Default::default()
+                },
+            );
+        }
+    });
+}
+
 #[component]
-fn Details(
-    array_access: RwSignal<usize>,
-    array_swap: RwSignal<usize>,
-    array_cmp: RwSignal<usize>,
+fn Timeline(
+    run: StoredValue<Option<Run>>,
+    play: RwSignal<bool>,
+    step: RwSignal<usize>,
+    first_step: RwSignal<usize>,
+    last_step: RwSignal<usize>,
 ) -> impl IntoView {
+    let seek = move |position: usize| {
+        let mut current = None;
+        run.update_value(|run| {
+            if let Some(run) = run.as_mut() {
+                run.history.seek(position);
+                run.redraw();
+                current = Some(run.history.position());
+            }
+        });
+        if let Some(current) = current {
+            step.set(current);
+        }
+    };
+
     view! {
-        <div class="ps-2">"Array accesses: "{move || array_access.get()}</div>
-        <div class="ps-2">"Array updates: "{move || array_swap.get()}</div>
-        <div class="ps-2">"Comparisons: "{move || array_cmp.get()}</div>
+        <div class="d-flex justify-content-start align-items-center col-11 px-2 mb-2">
+            // step back
+            <button class="btn btn-outline-secondary btn-sm me-2"
+                disabled=move || play.get() || step.get() <= first_step.get()
+                on:click=move |_| seek(step.get_untracked().saturating_sub(1))>
+                <i class="bi bi-chevron-left"></i>
+            </button>
+            // scrub
+            <input type="range" class="form-range mx-2" step="1"
+                min=move || first_step.get()
+                max=move || last_step.get()
+                disabled=move || play.get() || last_step.get() == 0
+                prop:value=move || step.get()
+                on:input=move |ev| seek(event_target_value(&ev).parse().expect("integer"))/>
+            // step forward
+            <button class="btn btn-outline-secondary btn-sm mx-2"
+                disabled=move || play.get() || last_step.get() <= step.get()
+                on:click=move |_| seek(step.get_untracked() + 1)>
+                <i class="bi bi-chevron-right"></i>
+            </button>
+            <span class="text-muted text-nowrap ms-2">
+                "Step "{move || step.get()}" / "{move || last_step.get()}
+            </span>
+        </div>
+    }
+}
+
+/// Pseudocode of the sort, with the line of the latest step highlighted.
+#[component]
+fn Pseudocode(
+    sort: Sort,
+    line: RwSignal<Option<usize>>,
+    step: RwSignal<usize>,
+    last_step: RwSignal<usize>,
+) -> impl IntoView {
+    // the line is only known for the latest step, not for rewound ones
+    let active = move || line.get().filter(|_| step.get() == last_step.get());
+    view! {
+        <pre class="col-3 ms-3 mb-0 p-2 border border-1 rounded border-secondary overflow-auto">
+            {sort
+                .pseudocode()
+                .iter()
+                .enumerate()
+                .map(|(i, text)| view! {
+                    <div class="px-1" class:bg-danger=move || active() == Some(i)>
+                        {*text}
+                    </div>
+                })
+                .collect_view()}
+        </pre>
+    }
+}
+
+/// Pending calls of a recursive sort, the current one first.
+#[component]
+fn Stack(
+    frames: RwSignal<Vec<Frame>>,
+    max_depth: RwSignal<usize>,
+    step: RwSignal<usize>,
+    last_step: RwSignal<usize>,
+) -> impl IntoView {
+    /// Frames listed before the rest are summed up
+    const SHOWN: usize = 10;
+
+    // the stack is only known for the latest step, not for rewound ones
+    let latest = move || step.get() == last_step.get();
+    view! {
+        <div class="col-3 ms-3 p-2 border border-1 rounded border-secondary overflow-auto">
+            <div class="text-muted mb-1">
+                "Depth "
+                {move || frames.with(|frames| frames.last().map_or(0, |frame| frame.depth))}
+                " / max "{move || max_depth.get()}
+            </div>
+            <Show when=latest fallback=|| view! { <div class="text-muted">"Rewound"</div> }>
+                {move || frames.with(|frames| {
+                    let hidden = frames.len().saturating_sub(SHOWN);
+                    let shown = frames
+                        .iter()
+                        .rev()
+                        .take(SHOWN)
+                        .enumerate()
+                        .map(|(i, frame)| view! {
+                            <div class="font-monospace small" class:text-success=i == 0
+                                style=format!("padding-left: {}rem;", frame.depth as f64 * 0.5)>
+                                {format!("[{}, {})", frame.start, frame.end)}
+                            </div>
+                        })
+                        .collect_view();
+                    view! {
+                        {shown}
+                        {(hidden > 0).then(|| view! {
+                            <div class="text-muted small">{hidden}" more"</div>
+                        })}
+                    }
+                })}
+            </Show>
+        </div>
     }
 }
 
 #[component]
-fn Controls(
+fn CustomInput<R>(
+    play: RwSignal<bool>,
+    custom: RwSignal<Option<Vec<usize>>>,
+    reset: R,
+) -> impl IntoView
+where
+    R: Fn() + Copy + 'static,
+{
+    let text = create_rw_signal(String::new());
+    let error = create_rw_signal(None::<String>);
+
+    let apply = move || match input::parse(&text.get_untracked(), MAX_ITEMS) {
+        Ok(data) => {
+            error.set(None);
+            custom.set(Some(data));
+            reset();
+        }
+        Err(err) => error.set(Some(err.to_string())),
+    };
+
+    let clear = move || {
+        error.set(None);
+        custom.set(None);
+        reset();
+    };
+
+    let import = move |ev: ev::Event| {
+        let input = event_target::<web_sys::HtmlInputElement>(&ev);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        let reader = web_sys::FileReader::new().expect("to create file reader");
+        let onload = Closure::once_into_js(move |ev: web_sys::Event| {
+            let content = ev
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::FileReader>().ok())
+                .and_then(|reader| reader.result().ok())
+                .and_then(|content| content.as_string());
+            match content {
+                Some(content) => {
+                    text.set(content);
+                    apply();
+                }
+                None => error.set(Some("Could not read the file as text".to_string())),
+            }
+        });
+        reader.set_onload(Some(onload.unchecked_ref()));
+        if reader.read_as_text(&file).is_err() {
+            error.set(Some("Could not read the file".to_string()));
+        }
+        // allow importing the same file again
+        input.set_value("");
+    };
+
+    view! {
+        <div class="d-flex justify-content-start align-items-start col-11 px-2 mb-2">
+            <textarea class="form-control form-control-sm me-2" rows="1"
+                placeholder="Custom input, e.g. 5, 2, 4, 6, 1, 3 or [5, 2, 4, 6, 1, 3]"
+                disabled=move || play.get()
+                prop:value=move || text.get()
+                on:input=move |ev| text.set(event_target_value(&ev))>
+            </textarea>
+            <label class="btn btn-sm btn-outline-secondary text-nowrap me-2"
+                class:disabled=move || play.get()>
+                <i class="bi bi-upload me-2"></i>
+                Import
+                <input type="file" class="d-none" accept=".txt,.csv,.json"
+                    on:change=import/>
+            </label>
+            <button class="btn btn-sm btn-outline-success text-nowrap me-2"
+                disabled=move || play.get()
+                on:click=move |_| apply()>
+                Use input
+            </button>
+            <button class="btn btn-sm btn-outline-secondary text-nowrap"
+                disabled=move || play.get() || custom.with(Option::is_none)
+                on:click=move |_| clear()>
+                Clear
+            </button>
+        </div>
+        <div class="px-2 mb-2">
+            {move || match (error.get(), custom.with(|data| data.as_ref().map(Vec::len))) {
+                (Some(error), _) => view! { <span class="text-danger">{error}</span> }.into_view(),
+                (None, Some(len)) => view! {
+                    <span class="text-muted">"Using custom input of "{len}" items"</span>
+                }.into_view(),
+                (None, None) => ().into_view(),
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn Details(counters: RwSignal<Counters>) -> impl IntoView {
+    view! {
+        <div class="ps-2">"Array accesses: "{move || counters.get().accesses}</div>
+        <div class="ps-2">"Array updates: "{move || counters.get().updates}</div>
+        <div class="ps-2">"Comparisons: "{move || counters.get().comparisons}</div>
+    }
+}
+
+/// Position of the items slider for a number of items
+fn slider_position(items: usize) -> f64 {
+    ((items as f64).log10() * SLIDER_DECADE).round()
+}
+
+/// Number of items at a position of the items slider
+fn slider_items(position: f64) -> usize {
+    (10f64.powf(position / SLIDER_DECADE).round() as usize).clamp(1, MAX_ITEMS)
+}
+
+#[component]
+fn Controls<S, R>(
     play: RwSignal<bool>,
     update_ms: RwSignal<usize>,
     items: RwSignal<usize>,
-    volume: RwSignal<f32>,
+    seed: RwSignal<u64>,
+    distribution: RwSignal<Distribution>,
+    custom: RwSignal<Option<Vec<usize>>>,
+    #[prop(optional)] stability: Option<RwSignal<bool>>,
+    #[prop(optional)] sound: Option<Sound>,
+    #[prop(optional)] render: Option<RwSignal<Render>>,
     draw: Callback,
-) -> impl IntoView {
+    step_by: S,
+    reset: R,
+) -> impl IntoView
+where
+    S: Fn(usize) + Copy + 'static,
+    R: Fn() + Copy + 'static,
+{
+    let steps = create_rw_signal(10);
+    // generated input settings do not apply to custom input
+    let fixed = move || play.get() || custom.with(Option::is_some);
     let window = web_sys::window().expect("window should exists");
     let draw_to_canvas = move |_| {
         play.set(true);
@@ -252,7 +954,7 @@ fn Controls(
     };
 
     view! {
-        <div class="d-flex justify-content-start mb-3">
+        <div class="d-flex flex-wrap row-gap-2 justify-content-start mb-3">
             // play
             <button class="col-1 btn btn-outline-danger mx-2"
                 disabled=move || play.get()
@@ -260,32 +962,238 @@ fn Controls(
                 <i class="bi bi-play-fill me-2"></i>
                 Play
             </button>
-            // stop
+            // pause
             <button class="col-1 btn mx-2"
                 disabled=move || !play.get()
                 class:btn-outline-warning=move || play.get()
                 class:btn-outline-secondary=move || !play.get()
                 on:click=move |_| play.set(false)>
-                <i class="bi bi-stop-fill me-2"></i>
-                Stop
+                <i class="bi bi-pause-fill me-2"></i>
+                Pause
+            </button>
+            // step
+            <button class="col-1 btn btn-outline-secondary mx-2"
+                disabled=move || play.get()
+                on:click=move |_| step_by(1)>
+                <i class="bi bi-skip-end-fill me-2"></i>
+                Step
+            </button>
+            // step n
+            <span class="d-inline-flex input-group w-auto mx-2">
+                <input type="number" class="form-control" style="width: 6rem;" min="1"
+                    disabled=move || play.get()
+                    prop:value=steps.get_untracked()
+                    on:input=move |ev| {
+                        if let Ok(n) = event_target_value(&ev).parse::<usize>() {
+                            steps.set(n.max(1));
+                        }
+                    }/>
+                <button class="btn btn-outline-secondary"
+                    disabled=move || play.get()
+                    on:click=move |_| step_by(steps.get_untracked())>
+                    "Step "{move || steps.get()}
+                </button>
+            </span>
+            // reset
+            <button class="col-1 btn btn-outline-secondary mx-2"
+                on:click=move |_| reset()>
+                <i class="bi bi-arrow-counterclockwise me-2"></i>
+                Reset
             </button>
             // items
             <span class="d-inline-flex flex-column border rounded p-2 mx-2"
-                class:border-success=move || !play.get()
-                class:border-secondary=move || play.get()>
+                class:border-success=move || !fixed()
+                class:border-secondary=fixed>
                 <label class="text-muted me-2">"Items: "{move || items.get()}</label>
-                <input type="range" class="form-range" min="1" max="5000" step="1"
-                    disabled=move || play.get()
-                    prop:value=items.get_untracked()
-                    on:input=move |ev| items.set(event_target_value(&ev).parse().expect("integer"))/>
+                // logarithmic, so both a few and a million items are in reach
+                <input type="range" class="form-range" min="0" max=slider_position(MAX_ITEMS) step="1"
+                    disabled=fixed
+                    prop:value=move || slider_position(items.get())
+                    on:input=move |ev| {
+                        let position: f64 = event_target_value(&ev).parse().expect("number");
+                        items.set(slider_items(position));
+                        reset();
+                    }/>
             </span>
-            // volume
-            <span class="d-inline-flex flex-column border border-success rounded p-2 mx-2">
-                <label class="text-muted me-2">"Volume: "{move || (volume.get() * 100.0).floor()}%</label>
-                <input type="range" class="form-range" min="0" max="100" step="1"
-                    prop:value=volume.with_untracked(|v| (v * 100.0).floor())
-                    on:input=move |ev| volume.set(event_target_value(&ev).parse::<f32>().expect("f32") / 100.0)/>
+            // distribution
+            <span class="d-inline-flex flex-column border rounded p-2 mx-2"
+                class:border-success=move || !fixed()
+                class:border-secondary=fixed>
+                <label class="text-muted me-2">"Input"</label>
+                <select class="form-select form-select-sm"
+                    disabled=fixed
+                    on:change=move |ev| {
+                        if let Some(value) = Distribution::from_key(&event_target_value(&ev)) {
+                            distribution.set(value);
+                            reset();
+                        }
+                    }>
+                    {Distribution::ALL
+                        .into_iter()
+                        .map(|d| view! {
+                            <option value=d.key_as_str() prop:selected=move || distribution.get() == d>
+                                {d.name_as_str()}
+                            </option>
+                        })
+                        .collect_view()}
+                </select>
+            </span>
+            // seed
+            <span class="d-inline-flex flex-column border rounded p-2 mx-2"
+                class:border-success=move || !fixed()
+                class:border-secondary=fixed>
+                <label class="text-muted me-2">"Seed"</label>
+                <span class="input-group input-group-sm">
+                    <input type="number" class="form-control" style="width: 8rem;" min="0"
+                        disabled=fixed
+                        prop:value=move || seed.get().to_string()
+                        on:change=move |ev| {
+                            if let Ok(value) = event_target_value(&ev).parse() {
+                                seed.set(value);
+                                reset();
+                            }
+                        }/>
+                    <button class="btn btn-outline-secondary" title="New seed"
+                        disabled=fixed
+                        on:click=move |_| {
+                            seed.set(input::random_seed());
+                            reset();
+                        }>
+                        <i class="bi bi-dice-5"></i>
+                    </button>
+                </span>
             </span>
+            // stability mode, for views that tag equal keys
+            {stability.map(|stability| view! {
+                <span class="d-inline-flex flex-column border rounded p-2 mx-2"
+                    class:border-success=move || !play.get()
+                    class:border-secondary=move || play.get()>
+                    <label class="text-muted me-2">"Stability"</label>
+                    <div class="form-check form-switch">
+                        <input type="checkbox" class="form-check-input" role="switch"
+                            title="Duplicate keys, shaded by their order in the input"
+                            disabled=move || play.get()
+                            prop:checked=move || stability.get()
+                            on:change=move |ev| {
+                                stability.set(event_target_checked(&ev));
+                                reset();
+                            }/>
+                    </div>
+                </span>
+            })}
+            // sound, for views heard as well
+            {sound.map(|sound| view! {
+                <span class="d-inline-flex flex-column border border-success rounded p-2 mx-2">
+                    <label class="text-muted me-2">"Volume: "{move || (sound.volume.get() * 100.0).floor()}%</label>
+                    <span class="d-inline-flex align-items-center">
+                        <button class="btn btn-sm btn-outline-secondary me-2"
+                            title=move || if sound.muted.get() { "Unmute" } else { "Mute" }
+                            on:click=move |_| sound.muted.update(|muted| *muted = !*muted)>
+                            <i class="bi"
+                                class:bi-volume-mute-fill=move || sound.muted.get()
+                                class:bi-volume-up-fill=move || !sound.muted.get()></i>
+                        </button>
+                        <input type="range" class="form-range" min="0" max="100" step="1"
+                            disabled=move || sound.muted.get()
+                            prop:value=sound.volume.with_untracked(|v| (v * 100.0).floor())
+                            on:input=move |ev| sound.volume.set(event_target_value(&ev).parse::<f32>().expect("f32") / 100.0)/>
+                    </span>
+                </span>
+                <span class="d-inline-flex flex-column border border-success rounded p-2 mx-2">
+                    <label class="text-muted me-2">"Waveform"</label>
+                    <select class="form-select form-select-sm"
+                        on:change=move |ev| {
+                            if let Some(value) = Waveform::from_key(&event_target_value(&ev)) {
+                                sound.waveform.set(value);
+                            }
+                        }>
+                        {Waveform::ALL
+                            .into_iter()
+                            .map(|w| view! {
+                                <option value=w.key_as_str() prop:selected=move || sound.waveform.get() == w>
+                                    {w.name_as_str()}
+                                </option>
+                            })
+                            .collect_view()}
+                    </select>
+                </span>
+                <span class="d-inline-flex flex-column border border-success rounded p-2 mx-2">
+                    <label class="text-muted me-2">"Pitch"</label>
+                    <span class="input-group input-group-sm">
+                        <input type="number" class="form-control" style="width: 5.5rem;" title="Lowest frequency"
+                            min=Pitch::LOWEST_HZ max=Pitch::HIGHEST_HZ
+                            prop:value=move || sound.pitch.with(|p| p.min_hz)
+                            on:change=move |ev| {
+                                if let Ok(hz) = event_target_value(&ev).parse::<f64>() {
+                                    sound.pitch.update(|p| p.min_hz = hz.clamp(Pitch::LOWEST_HZ, p.max_hz));
+                                }
+                            }/>
+                        <span class="input-group-text">"to"</span>
+                        <input type="number" class="form-control" style="width: 5.5rem;" title="Highest frequency"
+                            min=Pitch::LOWEST_HZ max=Pitch::HIGHEST_HZ
+                            prop:value=move || sound.pitch.with(|p| p.max_hz)
+                            on:change=move |ev| {
+                                if let Ok(hz) = event_target_value(&ev).parse::<f64>() {
+                                    sound.pitch.update(|p| p.max_hz = hz.clamp(p.min_hz, Pitch::HIGHEST_HZ));
+                                }
+                            }/>
+                        <span class="input-group-text">"Hz"</span>
+                    </span>
+                    <span class="d-inline-flex mt-1">
+                        <select class="form-select form-select-sm me-1" title="Spread of values over the range"
+                            on:change=move |ev| {
+                                if let Some(value) = Mapping::from_key(&event_target_value(&ev)) {
+                                    sound.pitch.update(|p| p.mapping = value);
+                                }
+                            }>
+                            {Mapping::ALL
+                                .into_iter()
+                                .map(|m| view! {
+                                    <option value=m.key_as_str() prop:selected=move || sound.pitch.with(|p| p.mapping == m)>
+                                        {m.name_as_str()}
+                                    </option>
+                                })
+                                .collect_view()}
+                        </select>
+                        <select class="form-select form-select-sm" title="Scale notes are rounded to"
+                            on:change=move |ev| {
+                                if let Some(value) = Tuning::from_key(&event_target_value(&ev)) {
+                                    sound.pitch.update(|p| p.tuning = value);
+                                }
+                            }>
+                            {Tuning::ALL
+                                .into_iter()
+                                .map(|t| view! {
+                                    <option value=t.key_as_str() prop:selected=move || sound.pitch.with(|p| p.tuning == t)>
+                                        {t.name_as_str()}
+                                    </option>
+                                })
+                                .collect_view()}
+                        </select>
+                    </span>
+                </span>
+            })}
+            // render layout, for views drawn on a canvas
+            {render.map(|render| view! {
+                <span class="d-inline-flex flex-column border border-success rounded p-2 mx-2">
+                    <label class="text-muted me-2">"Render"</label>
+                    <select class="form-select form-select-sm"
+                        on:change=move |ev| {
+                            if let Some(value) = Render::from_key(&event_target_value(&ev)) {
+                                render.set(value);
+                            }
+                        }>
+                        {Render::ALL
+                            .into_iter()
+                            .map(|r| view! {
+                                <option value=r.key_as_str() prop:selected=move || render.get() == r>
+                                    {r.name_as_str()}
+                                </option>
+                            })
+                            .collect_view()}
+                    </select>
+                </span>
+            })}
             // update ms
             <span class="d-inline-flex flex-column border border-success rounded p-2 mx-2">
                 <label class="text-muted me-2">"Delay "{move || update_ms.get()}"ms"</label>
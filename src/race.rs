@@ -0,0 +1,356 @@
+use crate::canvas::{Canvas, Render};
+use crate::run::steps_per_tick;
+use crate::{input_data, link_input, Callback, Controls, CustomInput};
+use leptos::*;
+use leptos_router::*;
+use leptos_sort::visual_sort::input::Distribution;
+use leptos_sort::visual_sort::{Color, Counters, Scale, Sort, SortBase, VisualSort};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::{prelude::Closure, JsCast};
+
+pub const ROUTE: &str = "/race";
+
+const MIN_LANES: usize = 2;
+
+/// What the page shows of one sort in the race.
+#[derive(Copy, Clone)]
+struct LaneView {
+    sort: Sort,
+    canvas_ref: NodeRef<html::Canvas>,
+    counters: RwSignal<Counters>,
+    steps: RwSignal<usize>,
+    place: RwSignal<Option<usize>>,
+}
+
+impl LaneView {
+    fn clear(&self) {
+        self.counters.set(Counters::default());
+        self.steps.set(0);
+        self.place.set(None);
+    }
+}
+
+/// One sort in a race, stepped on the shared clock.
+struct Lane {
+    view: LaneView,
+    sorter: Box<dyn VisualSort>,
+    canvas: Canvas,
+    counters: Counters,
+    steps: usize,
+}
+
+impl Lane {
+    fn draw(&mut self, ticks: usize) {
+        let mut changed = Vec::new();
+        for _ in 0..ticks {
+            if self.sorter.done() {
+                break;
+            }
+            self.sorter.update();
+            for op in self.sorter.base_mut().take_ops() {
+                self.counters.add(&op);
+                changed.extend(op.changes());
+            }
+            self.steps += 1;
+        }
+        let done = self.sorter.done();
+        let highlights = if done {
+            Vec::new()
+        } else {
+            self.sorter.highlights()
+        };
+        let data = self.sorter.base().data();
+        self.canvas.draw(data, Some(&changed), &highlights, |i| {
+            if done {
+                Color::Red
+            } else {
+                self.sorter.color(i)
+            }
+        });
+    }
+}
+
+#[component]
+pub fn Race(
+    play: RwSignal<bool>,
+    update_ms: RwSignal<usize>,
+    items: RwSignal<usize>,
+    seed: RwSignal<u64>,
+    distribution: RwSignal<Distribution>,
+    custom: RwSignal<Option<Vec<usize>>>,
+    render: RwSignal<Render>,
+) -> impl IntoView {
+    let selected = create_rw_signal(vec![
+        Sort::Bubble,
+        Sort::Insertion,
+        Sort::Merge,
+        Sort::Quick,
+    ]);
+    let views = store_value(
+        Sort::ALL
+            .into_iter()
+            .map(|sort| LaneView {
+                sort,
+                canvas_ref: create_node_ref(),
+                counters: create_rw_signal(Counters::default()),
+                steps: create_rw_signal(0),
+                place: create_rw_signal(None),
+            })
+            .collect::<Vec<_>>(),
+    );
+    let lanes = store_value(Vec::<Lane>::new());
+    let finishers = create_rw_signal(Vec::<Sort>::new());
+    let mut prev_update = 0.0;
+
+    let window = web_sys::window().unwrap();
+    let document = leptos::document();
+    let location = use_location();
+    let start_loc = location.pathname.get_untracked();
+
+    let finished = move || lanes.with_value(|lanes| lanes.iter().all(|lane| lane.sorter.done()));
+
+    // continue a paused race, or line up the selected sorts on the same input
+    let prepare = move || {
+        if !finished() {
+            return;
+        }
+        let data = input_data(items, seed, distribution, custom);
+        let scale = Scale::new(&data);
+        let selected = selected.get_untracked();
+        let new_lanes = views.with_value(|views| {
+            views
+                .iter()
+                .filter(|view| selected.contains(&view.sort))
+                .map(|view| {
+                    view.clear();
+                    Lane {
+                        view: *view,
+                        sorter: view.sort.init(SortBase::new(data.clone())),
+                        canvas: Canvas::new(
+                            &view.canvas_ref,
+                            data.len(),
+                            scale,
+                            render.get_untracked(),
+                        ),
+                        counters: Counters::default(),
+                        steps: 0,
+                    }
+                })
+                .collect()
+        });
+        lanes.set_value(new_lanes);
+        finishers.set(Vec::new());
+    };
+
+    // advance every lane by the same ticks, true when all have finished
+    let advance = move |ticks: usize| {
+        let mut drawn = Vec::new();
+        let mut finished_now = Vec::new();
+        lanes.update_value(|lanes| {
+            for lane in lanes.iter_mut().filter(|lane| !lane.sorter.done()) {
+                lane.draw(ticks);
+                drawn.push((lane.view, lane.counters, lane.steps));
+                if lane.sorter.done() {
+                    finished_now.push((lane.steps, lane.view));
+                }
+            }
+        });
+        for (view, counters, steps) in drawn {
+            view.counters.set(counters);
+            view.steps.set(steps);
+        }
+        // lanes finishing on the same frame are ordered by the steps they took
+        finished_now.sort_by_key(|(steps, _)| *steps);
+        for (_, view) in finished_now {
+            finishers.update(|finishers| {
+                finishers.push(view.sort);
+                view.place.set(Some(finishers.len()));
+            });
+        }
+        finished()
+    };
+
+    let step_by = move |ticks: usize| {
+        prepare();
+        advance(ticks);
+    };
+
+    let reset = move || {
+        play.set(false);
+        lanes.update_value(|lanes| {
+            for lane in lanes.drain(..) {
+                lane.canvas.clear();
+            }
+        });
+        views.with_value(|views| views.iter().for_each(LaneView::clear));
+        finishers.set(Vec::new());
+    };
+
+    link_input(ROUTE, items, seed, distribution, reset);
+
+    // every lane follows the layout, redrawn in place
+    create_effect(move |_| {
+        let render = render.get();
+        lanes.update_value(|lanes| {
+            for lane in lanes.iter_mut() {
+                lane.canvas.set_render(render);
+                lane.draw(0);
+            }
+        });
+    });
+
+    let toggle = move |sort: Sort| {
+        selected.update(|selected| match selected.iter().position(|&s| s == sort) {
+            Some(i) if selected.len() > MIN_LANES => {
+                selected.remove(i);
+            }
+            Some(_) => {}
+            None => selected.push(sort),
+        });
+        reset();
+    };
+
+    let draw: Callback = Rc::new(RefCell::new(Closure::new(move |_| ())));
+    let draw_clone = draw.clone();
+
+    *draw.borrow_mut() = Closure::new(move |prev_end_time| {
+        if prev_update == 0.0 {
+            prev_update = prev_end_time;
+            prepare();
+        }
+
+        let now = document.timeline().current_time().unwrap();
+        let delta = now - prev_update;
+        // every lane sorts the same input
+        let per_tick = lanes.with_value(|lanes| {
+            lanes
+                .first()
+                .map_or(1, |lane| steps_per_tick(lane.sorter.base().data().len()))
+        });
+        let ticks = delta as usize / update_ms.get_untracked() * per_tick;
+        let finished = if ticks > 0 {
+            prev_update = now;
+            advance(ticks)
+        } else {
+            finished()
+        };
+
+        if !finished && play.get_untracked() && start_loc == location.pathname.get_untracked() {
+            let _ = window.request_animation_frame(draw_clone.borrow().as_ref().unchecked_ref());
+        } else {
+            prev_update = 0.0;
+            play.set(false);
+        }
+    });
+
+    let lane_views = views.with_value(|views| {
+        views
+            .iter()
+            .map(|&view| view! { <RaceLane view selected/> })
+            .collect_view()
+    });
+
+    view! {
+        <div class="container-fluid my-3 p-4">
+            <h3 class="p-2">Race</h3>
+            <Controls play update_ms items seed distribution custom render draw step_by reset/>
+            <CustomInput play custom reset/>
+            <div class="d-flex flex-wrap justify-content-start align-items-center px-2 mb-3">
+                <span class="text-muted me-2">"Sorts"</span>
+                {Sort::ALL
+                    .into_iter()
+                    .map(|sort| {
+                        let chosen = move || selected.with(|selected| selected.contains(&sort));
+                        view! {
+                            <button class="btn btn-sm me-2"
+                                class:btn-danger=chosen
+                                class:btn-outline-secondary=move || !chosen()
+                                disabled=move || play.get()
+                                on:click=move |_| toggle(sort)>
+                                {sort.name_as_str()}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+            <div class="row row-cols-2 g-3 col-11">
+                {lane_views}
+            </div>
+            <Results views finishers/>
+        </div>
+    }
+}
+
+#[component]
+fn RaceLane(view: LaneView, selected: RwSignal<Vec<Sort>>) -> impl IntoView {
+    let LaneView {
+        sort,
+        canvas_ref,
+        counters,
+        steps,
+        place,
+    } = view;
+    view! {
+        <div class="col" class:d-none=move || selected.with(|selected| !selected.contains(&sort))>
+            <div class="d-flex justify-content-between px-2">
+                <span>{sort.name_as_str()}</span>
+                {move || place.get().map(|place| view! {
+                    <span class="badge bg-danger">"#"{place}</span>
+                })}
+            </div>
+            <canvas class="w-100 border border-1 rounded border-danger" style="height: 160px;"
+                _ref=canvas_ref />
+            <div class="text-muted small px-2">
+                "Steps: "{move || steps.get()}
+                " · Accesses: "{move || counters.get().accesses}
+                " · Updates: "{move || counters.get().updates}
+                " · Comparisons: "{move || counters.get().comparisons}
+            </div>
+        </div>
+    }
+}
+
+/// Finish order and totals of the sorts that are done.
+#[component]
+fn Results(views: StoredValue<Vec<LaneView>>, finishers: RwSignal<Vec<Sort>>) -> impl IntoView {
+    let rows = move || {
+        finishers
+            .get()
+            .into_iter()
+            .filter_map(|sort| {
+                views.with_value(|views| views.iter().find(|v| v.sort == sort).copied())
+            })
+            .enumerate()
+            .map(|(i, view)| {
+                let counters = view.counters.get();
+                view! {
+                    <tr>
+                        <td>{i + 1}</td>
+                        <td>{view.sort.name_as_str()}</td>
+                        <td>{view.steps.get()}</td>
+                        <td>{counters.accesses}</td>
+                        <td>{counters.updates}</td>
+                        <td>{counters.comparisons}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <table class="table table-sm col-11 mt-3" class:d-none=move || finishers.with(Vec::is_empty)>
+            <thead>
+                <tr>
+                    <th>"Place"</th>
+                    <th>"Sort"</th>
+                    <th>"Steps"</th>
+                    <th>"Accesses"</th>
+                    <th>"Updates"</th>
+                    <th>"Comparisons"</th>
+                </tr>
+            </thead>
+            <tbody>{rows}</tbody>
+        </table>
+    }
+}
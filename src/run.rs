@@ -0,0 +1,180 @@
+use crate::audio::Audio;
+use crate::canvas::{Calls, Canvas, Lanes, Tree};
+use leptos::StoredValue;
+use leptos_sort::visual_sort::recursion::CallTree;
+use leptos_sort::visual_sort::sound::Note;
+use leptos_sort::visual_sort::stability::Shades;
+use leptos_sort::visual_sort::{Color, Counters, History, Op, VisualSort};
+
+/// Steps kept for rewinding a run
+const HISTORY_STEPS: usize = 100_000;
+/// Items each step of the animation clock stands for, larger inputs take more steps a tick
+const ITEMS_PER_STEP: usize = 1000;
+
+/// Steps to take on each tick of the animation clock, so large inputs still finish in time.
+pub fn steps_per_tick(items: usize) -> usize {
+    (items / ITEMS_PER_STEP).max(1)
+}
+
+/// A sort in progress, with the canvas and audio it is drawn and heard through.
+pub struct Run {
+    pub sorter: Box<dyn VisualSort>,
+    pub canvas: Canvas,
+    /// Auxiliary buffers, for sorts that have them.
+    pub lanes: Option<Lanes>,
+    /// The heap as a tree, for sorts that build one and when asked for.
+    pub tree: Option<Tree>,
+    /// Calls of recursive sorts.
+    pub calls: Option<Calls>,
+    audio: StoredValue<Audio>,
+    pub counters: Counters,
+    pub history: History,
+    pub call_tree: CallTree,
+    /// Most values held in auxiliary buffers at once.
+    pub peak_aux: usize,
+    /// Colors of tagged elements in stability mode.
+    shades: Option<Shades>,
+}
+
+impl Run {
+    /// A run drawn on the canvas alone, the other views can be added after.
+    pub fn new(sorter: Box<dyn VisualSort>, canvas: Canvas, audio: StoredValue<Audio>) -> Self {
+        let history = History::new(sorter.base().data(), HISTORY_STEPS);
+        let shades = Shades::new(sorter.base());
+        let peak_aux = sorter.aux_len();
+        let mut call_tree = CallTree::new();
+        call_tree.update(&sorter.frames());
+        Self {
+            sorter,
+            canvas,
+            lanes: None,
+            tree: None,
+            calls: None,
+            audio,
+            counters: Counters::default(),
+            history,
+            call_tree,
+            peak_aux,
+            shades,
+        }
+    }
+
+    /// Sorted, and not rewound to an earlier step.
+    pub fn finished(&self) -> bool {
+        self.sorter.done() && self.history.at_end()
+    }
+
+    /// Advance by ticks steps, replaying rewound steps before stepping the sort.
+    pub fn draw(&mut self, ticks: usize) {
+        let mut notes = Vec::new();
+        let mut changed = Vec::new();
+        for _ in 0..ticks {
+            if self.history.forward() {
+                changed.extend(self.history.ops().flat_map(Op::changes));
+                continue;
+            }
+            if self.sorter.done() {
+                break;
+            }
+            self.sorter.update();
+            self.peak_aux = self.peak_aux.max(self.sorter.aux_len());
+            // only recursive sorts have calls to follow
+            if self.calls.is_some() {
+                self.call_tree.update(&self.sorter.frames());
+            }
+            let ops = self.sorter.base_mut().take_ops();
+            let base = self.sorter.base();
+            for op in &ops {
+                self.counters.add(op);
+                changed.extend(op.changes());
+                notes.extend(Note::of(op, base));
+            }
+            self.history.record(ops);
+        }
+        self.audio.with_value(|audio| audio.play(&notes));
+        self.paint(Some(&changed));
+    }
+
+    /// Clear the canvas and every other view.
+    pub fn clear(&self) {
+        self.canvas.clear();
+        if let Some(lanes) = &self.lanes {
+            lanes.clear();
+        }
+        if let Some(tree) = &self.tree {
+            tree.clear();
+        }
+        if let Some(calls) = &self.calls {
+            calls.clear();
+        }
+    }
+
+    /// Draw the data at the current position in the history.
+    pub fn redraw(&self) {
+        self.paint(None);
+    }
+
+    /// Draw the data, given the indices changed since last drawn when they are known
+    fn paint(&self, changed: Option<&[usize]>) {
+        if self.history.at_end() {
+            let done = self.sorter.done();
+            let data = self.sorter.base().data();
+            let highlights = if done {
+                Vec::new()
+            } else {
+                self.sorter.highlights()
+            };
+            self.canvas
+                .draw(data, changed, &highlights, |i| match self.sorter.color(i) {
+                    color @ (Color::Green | Color::Light) if !done => color,
+                    _ => self.resting(data[i]),
+                });
+            if let Some(lanes) = &self.lanes {
+                lanes.draw(&self.sorter.aux());
+            }
+            if let (Some(tree), Some(heap)) = (&self.tree, self.sorter.heap()) {
+                let swaps: Vec<(usize, usize)> = self
+                    .history
+                    .ops()
+                    .filter_map(|op| match *op {
+                        Op::Swap(i, j) => Some((i, j)),
+                        _ => None,
+                    })
+                    .collect();
+                tree.draw(data, heap, &swaps);
+            }
+            if let Some(calls) = &self.calls {
+                calls.draw(&self.call_tree, self.sorter.frames().last());
+            }
+        } else {
+            // highlight what the step touched, sort state is only known for the latest step
+            let mut touched: Vec<usize> = self.history.touched().collect();
+            touched.sort_unstable();
+            let data = self.history.data();
+            self.canvas.draw(data, changed, &touched, |i| {
+                if touched.binary_search(&i).is_ok() {
+                    Color::Light
+                } else {
+                    self.resting(data[i])
+                }
+            });
+            // buffers and the heap are only known for the latest step
+            if let Some(lanes) = &self.lanes {
+                lanes.clear();
+            }
+            if let Some(tree) = &self.tree {
+                tree.clear();
+            }
+            if let Some(calls) = &self.calls {
+                calls.clear();
+            }
+        }
+    }
+
+    /// Color of a value the sort is not working on.
+    fn resting(&self, value: usize) -> Color {
+        self.shades
+            .as_ref()
+            .map_or(Color::Red, |shades| shades.color(value))
+    }
+}
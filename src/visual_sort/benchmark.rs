@@ -0,0 +1,190 @@
+use super::input::Distribution;
+use super::{Counters, Sort, SortBase, VisualSort};
+
+/// Input sizes swept by default
+pub const SIZES: [usize; 4] = [10, 100, 1_000, 10_000];
+
+/// Distributions swept by default
+pub const DISTRIBUTIONS: [Distribution; 4] = [
+    Distribution::Shuffled,
+    Distribution::Sorted,
+    Distribution::Reversed,
+    Distribution::FewUnique,
+];
+
+/// Totals of one sort run to completion on one input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub sort: Sort,
+    pub distribution: Distribution,
+    pub items: usize,
+    /// Span of the input values, max - min + 1.
+    pub range: usize,
+    pub counters: Counters,
+    pub steps: usize,
+}
+
+/// Sort, distribution and size of one measurement
+type Cell = (Sort, Distribution, usize);
+
+/// A cell being measured
+struct Measuring {
+    cell: Cell,
+    sorter: Box<dyn VisualSort>,
+    counters: Counters,
+    steps: usize,
+}
+
+impl Measuring {
+    fn new((sort, distribution, items): Cell, seed: u64) -> Self {
+        let mut sorter = sort.init(SortBase::new(distribution.generate(items, seed)));
+        let mut counters = Counters::default();
+        // some sorts already work on the data when created
+        for op in sorter.base_mut().take_ops() {
+            counters.add(&op);
+        }
+        Self {
+            cell: (sort, distribution, items),
+            sorter,
+            counters,
+            steps: 0,
+        }
+    }
+
+    /// Step up to budget times, returning the measurement once sorted
+    fn work(&mut self, budget: usize) -> Option<Measurement> {
+        for _ in 0..budget {
+            if self.sorter.done() {
+                break;
+            }
+            self.sorter.update();
+            for op in self.sorter.base_mut().take_ops() {
+                self.counters.add(&op);
+            }
+            self.steps += 1;
+        }
+        self.sorter.done().then(|| {
+            let (sort, distribution, items) = self.cell;
+            let scale = self.sorter.base().scale();
+            Measurement {
+                sort,
+                distribution,
+                items,
+                range: scale.max - scale.min + 1,
+                counters: self.counters,
+                steps: self.steps,
+            }
+        })
+    }
+}
+
+/// Every sort over a sweep of sizes and distributions, measured without drawing or sound.
+///
+/// The work is split into budgets of steps so a page can measure a little each frame.
+pub struct Benchmark {
+    cells: Vec<Cell>,
+    next: usize,
+    seed: u64,
+    current: Option<Measuring>,
+}
+
+impl Benchmark {
+    pub fn new(sorts: &[Sort], distributions: &[Distribution], sizes: &[usize], seed: u64) -> Self {
+        let mut cells = Vec::new();
+        for &items in sizes {
+            for &distribution in distributions {
+                for &sort in sorts {
+                    cells.push((sort, distribution, items));
+                }
+            }
+        }
+        Self {
+            cells,
+            next: 0,
+            seed,
+            current: None,
+        }
+    }
+
+    /// Number of measurements in the sweep.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Number of measurements finished so far.
+    pub fn completed(&self) -> usize {
+        self.next - usize::from(self.current.is_some())
+    }
+
+    pub fn done(&self) -> bool {
+        self.current.is_none() && self.next == self.cells.len()
+    }
+
+    /// Step the current measurement by up to budget steps, returning it when finished.
+    pub fn work(&mut self, budget: usize) -> Option<Measurement> {
+        if self.current.is_none() {
+            let cell = *self.cells.get(self.next)?;
+            self.current = Some(Measuring::new(cell, self.seed));
+            self.next += 1;
+        }
+        let measurement = self.current.as_mut()?.work(budget);
+        if measurement.is_some() {
+            self.current = None;
+        }
+        measurement
+    }
+}
+
+/// Run one sort to completion on a generated input.
+pub fn measure(sort: Sort, distribution: Distribution, items: usize, seed: u64) -> Measurement {
+    Measuring::new((sort, distribution, items), seed)
+        .work(usize::MAX)
+        .expect("sort to finish")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::run;
+
+    #[test]
+    fn counts_every_op_of_the_run() {
+        for sort in Sort::ALL {
+            for distribution in DISTRIBUTIONS {
+                let measurement = measure(sort, distribution, 50, 3);
+                let mut sorter = sort.init(SortBase::new(distribution.generate(50, 3)));
+                let mut counters = Counters::default();
+                run(sorter.as_mut()).iter().for_each(|op| counters.add(op));
+                assert_eq!(
+                    measurement.counters, counters,
+                    "{sort:?} on {distribution:?}"
+                );
+                assert!(measurement.steps > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn small_budgets_measure_the_same() {
+        let sizes = [1, 10, 40];
+        let mut benchmark = Benchmark::new(&Sort::ALL, &DISTRIBUTIONS, &sizes, 9);
+        assert_eq!(
+            benchmark.len(),
+            Sort::ALL.len() * DISTRIBUTIONS.len() * sizes.len()
+        );
+
+        let mut measurements = Vec::new();
+        while !benchmark.done() {
+            measurements.extend(benchmark.work(7));
+            assert_eq!(benchmark.completed(), measurements.len());
+        }
+        assert_eq!(measurements.len(), benchmark.len());
+        for m in measurements {
+            assert_eq!(m, measure(m.sort, m.distribution, m.items, 9));
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+/// Smallest, largest and total value of the items in one column.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bin {
+    pub min: usize,
+    pub max: usize,
+    /// Wide enough for many large values, as usize is 32 bits on the web.
+    pub sum: u64,
+    pub len: usize,
+}
+
+impl Bin {
+    fn new(values: &[usize]) -> Self {
+        Self {
+            min: values.iter().copied().min().unwrap_or(0),
+            max: values.iter().copied().max().unwrap_or(0),
+            sum: values.iter().map(|&v| v as u64).sum(),
+            len: values.len(),
+        }
+    }
+
+    pub fn avg(&self) -> usize {
+        (self.sum / self.len.max(1) as u64) as usize
+    }
+}
+
+/// Data gathered into a fixed number of columns, for more items than there are pixels.
+///
+/// Columns are kept up to date from the indices that changed, and remember which of
+/// them did until taken, so only those are drawn again.
+pub struct Bins {
+    bins: Vec<Bin>,
+    items: usize,
+    dirty: Vec<bool>,
+}
+
+impl Bins {
+    pub fn new(data: &[usize], columns: usize) -> Self {
+        let columns = columns.clamp(1, data.len().max(1));
+        let mut bins = Self {
+            bins: Vec::with_capacity(columns),
+            items: data.len(),
+            dirty: vec![true; columns],
+        };
+        for column in 0..columns {
+            let bin = Bin::new(&data[bins.range(column)]);
+            bins.bins.push(bin);
+        }
+        bins
+    }
+
+    pub fn len(&self) -> usize {
+        self.bins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    pub fn get(&self, column: usize) -> Bin {
+        self.bins[column]
+    }
+
+    /// Items gathered in a column, spread so columns differ by one item at most.
+    pub fn range(&self, column: usize) -> Range<usize> {
+        // in u64, as items times columns outgrows a 32 bit usize
+        let (columns, items) = (self.dirty.len() as u64, self.items as u64);
+        let start = column as u64 * items / columns;
+        let end = (column as u64 + 1) * items / columns;
+        start as usize..end as usize
+    }
+
+    /// Column an item is gathered in.
+    pub fn column(&self, i: usize) -> usize {
+        // the last column starting at or before the item
+        let (columns, items) = (self.dirty.len() as u64, self.items as u64);
+        (((i as u64 + 1) * columns - 1) / items) as usize
+    }
+
+    /// Gather the columns of the changed indices again.
+    pub fn update(&mut self, data: &[usize], changed: impl IntoIterator<Item = usize>) {
+        let mut columns: Vec<usize> = changed.into_iter().map(|i| self.column(i)).collect();
+        columns.sort_unstable();
+        columns.dedup();
+        for column in columns {
+            self.refresh(data, column);
+        }
+    }
+
+    /// Gather every column again, for data that may have changed anywhere.
+    pub fn update_all(&mut self, data: &[usize]) {
+        for column in 0..self.bins.len() {
+            self.refresh(data, column);
+        }
+    }
+
+    /// Mark a column to be drawn again, such as when its color changed.
+    pub fn mark(&mut self, column: usize) {
+        self.dirty[column] = true;
+    }
+
+    pub fn mark_all(&mut self) {
+        self.dirty.fill(true);
+    }
+
+    /// Columns that changed since last taken, in order.
+    pub fn take_dirty(&mut self) -> Vec<usize> {
+        let dirty = self
+            .dirty
+            .iter()
+            .enumerate()
+            .filter_map(|(column, &dirty)| dirty.then_some(column))
+            .collect();
+        self.dirty.fill(false);
+        dirty
+    }
+
+    fn refresh(&mut self, data: &[usize], column: usize) {
+        let bin = Bin::new(&data[self.range(column)]);
+        if bin != self.bins[column] {
+            self.bins[column] = bin;
+            self.dirty[column] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::inputs;
+    use crate::visual_sort::{Sort, SortBase};
+
+    #[test]
+    fn columns_cover_every_item_once() {
+        for (items, columns) in [(10, 3), (1000, 7), (5, 5), (800, 800), (1_000_003, 799)] {
+            let bins = Bins::new(&vec![0; items], columns);
+            let mut next = 0;
+            for column in 0..bins.len() {
+                let range = bins.range(column);
+                assert_eq!(range.start, next);
+                assert!(!range.is_empty());
+                assert_eq!(bins.column(range.start), column, "{items} in {columns}");
+                assert_eq!(bins.column(range.end - 1), column, "{items} in {columns}");
+                next = range.end;
+            }
+            assert_eq!(next, items);
+        }
+        // never more columns than items
+        assert_eq!(Bins::new(&[1, 2], 10).len(), 2);
+    }
+
+    #[test]
+    fn updates_follow_the_sort() {
+        for input in inputs().into_iter().filter(|input| input.len() > 8) {
+            let mut sorter = Sort::Quick.init(SortBase::new(input));
+            let mut bins = Bins::new(sorter.base().data(), 4);
+            bins.take_dirty();
+            while !sorter.done() {
+                sorter.update();
+                let changed: Vec<usize> = sorter
+                    .base_mut()
+                    .take_ops()
+                    .iter()
+                    .flat_map(|op| op.changes())
+                    .collect();
+                let before: Vec<Bin> = (0..bins.len()).map(|c| bins.get(c)).collect();
+                bins.update(sorter.base().data(), changed);
+                let dirty = bins.take_dirty();
+                let fresh = Bins::new(sorter.base().data(), 4);
+                for (column, before) in before.into_iter().enumerate() {
+                    assert_eq!(bins.get(column), fresh.get(column));
+                    // only columns that changed are drawn again
+                    let changed = before != fresh.get(column);
+                    assert_eq!(dirty.contains(&column), changed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bins_hold_min_max_and_avg() {
+        let mut bins = Bins::new(&[4, 1, 7, 2, 2, 8], 2);
+        assert_eq!(
+            bins.get(0),
+            Bin {
+                min: 1,
+                max: 7,
+                sum: 12,
+                len: 3
+            }
+        );
+        assert_eq!(bins.get(1).avg(), 4);
+        assert_eq!(bins.take_dirty(), [0, 1]);
+        bins.update_all(&[4, 1, 7, 2, 2, 9]);
+        assert_eq!(bins.take_dirty(), [1]);
+        bins.mark(0);
+        assert_eq!(bins.take_dirty(), [0]);
+    }
+}
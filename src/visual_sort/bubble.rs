@@ -1,55 +1,73 @@
 use super::{Color, SortBase, VisualSort};
-use leptos::*;
+
+pub(super) const PSEUDOCODE: [&str; 2] = [
+    "for x in 0..n, y in 0..n - x - 1: if a[y] > a[y + 1]",
+    "    swap a[y], a[y + 1]",
+];
+const COMPARE: usize = 0;
+const SWAP: usize = 1;
 
 pub struct Bubble {
     base: SortBase,
+    line: usize,
     x: usize,
     y: usize,
 }
 
 impl VisualSort for Bubble {
     fn new(base: SortBase) -> Self {
-        Self { base, x: 0, y: 0 }
+        Self {
+            base,
+            line: 0,
+            x: 0,
+            y: 0,
+        }
+    }
+
+    fn base(&self) -> &SortBase {
+        &self.base
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
+    fn color(&self, i: usize) -> Color {
+        // y has moved on to the second of the pair compared last
+        if i == self.y {
+            Color::Light
+        } else {
+            Color::Red
         }
+    }
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && i == self.y + 1 {
-                Color::Light.as_str()
-            } else {
-                Color::Red.as_str()
-            }
-        });
+    fn highlights(&self) -> Vec<usize> {
+        vec![self.y]
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
-        for x in self.x..self.base.data.len() {
-            self.x = x;
-            for y in self.y..self.base.data.len() - x - 1 {
-                self.y = y;
-                self.base.array_access.update(|n| *n += 2);
-                self.base.array_cmp.update(|n| *n += 1);
-                if self.base.data[y] > self.base.data[y + 1] {
-                    self.base.array_swap.update(|n| *n += 1);
-                    self.base.data.swap(y, y + 1);
-                    self.base.set_freq(self.base.data[y + 1]);
-                    return;
-                }
-            }
+        let n = self.base.data.len();
+        if self.x + 1 >= n {
+            self.base.done = true;
+            return;
+        }
+
+        // compare one pair each step, swapping it when out of order
+        let y = self.y;
+        self.line = COMPARE;
+        if self.base.cmp(y, y + 1).is_gt() {
+            self.base.swap(y, y + 1);
+            self.line = SWAP;
+        }
+        self.y += 1;
+        if self.y + 1 >= n - self.x {
+            self.x += 1;
             self.y = 0;
+            self.base.done = self.x + 1 >= n;
         }
-        self.base.done = true;
     }
 }
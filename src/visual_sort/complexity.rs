@@ -0,0 +1,193 @@
+/// Reference growth curves for counts measured at several input sizes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Linear,
+    Linearithmic,
+    Quadratic,
+    LinearRange,
+}
+
+impl Curve {
+    pub const ALL: [Curve; 4] = [
+        Self::Linear,
+        Self::Linearithmic,
+        Self::Quadratic,
+        Self::LinearRange,
+    ];
+
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Linear => "n",
+            Self::Linearithmic => "n log n",
+            Self::Quadratic => "n²",
+            Self::LinearRange => "n + k",
+        }
+    }
+
+    /// Shape of the curve for n items with values spanning a range of k.
+    pub fn shape(&self, n: f64, k: f64) -> f64 {
+        match self {
+            Self::Linear => n,
+            Self::Linearithmic => n * n.log2(),
+            Self::Quadratic => n * n,
+            Self::LinearRange => n + k,
+        }
+    }
+}
+
+/// A count measured for n items with values spanning a range of k.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub n: f64,
+    pub k: f64,
+    pub count: f64,
+}
+
+/// A curve scaled to follow samples.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub curve: Curve,
+    pub scale: f64,
+    /// Root mean square of the log10 ratios between samples and the fitted curve.
+    pub error: f64,
+}
+
+impl Fit {
+    pub fn at(&self, n: f64, k: f64) -> f64 {
+        self.scale * self.curve.shape(n, k)
+    }
+}
+
+/// Samples that have a logarithm on the curve, as log10 of the count and the shape
+fn log_pairs(curve: Curve, samples: &[Sample]) -> Vec<(f64, f64)> {
+    samples
+        .iter()
+        .map(|s| (s.count, curve.shape(s.n, s.k)))
+        .filter(|&(count, shape)| count > 0.0 && shape > 0.0)
+        .map(|(count, shape)| (count.log10(), shape.log10()))
+        .collect()
+}
+
+/// Scale a curve to the samples, weighting each sample by its ratio rather than its size.
+///
+/// None when no sample is usable, such as counts of zero.
+pub fn fit(curve: Curve, samples: &[Sample]) -> Option<Fit> {
+    let pairs = log_pairs(curve, samples);
+    if pairs.is_empty() {
+        return None;
+    }
+    let len = pairs.len() as f64;
+    let log_scale = pairs
+        .iter()
+        .map(|(count, shape)| count - shape)
+        .sum::<f64>()
+        / len;
+    let error = (pairs
+        .iter()
+        .map(|(count, shape)| (count - shape - log_scale).powi(2))
+        .sum::<f64>()
+        / len)
+        .sqrt();
+    Some(Fit {
+        curve,
+        scale: 10f64.powf(log_scale),
+        error,
+    })
+}
+
+/// Every reference curve scaled to the samples, the closest first.
+pub fn fits(samples: &[Sample]) -> Vec<Fit> {
+    let mut fits: Vec<Fit> = Curve::ALL
+        .into_iter()
+        .filter_map(|curve| fit(curve, samples))
+        .collect();
+    fits.sort_by(|a, b| a.error.total_cmp(&b.error));
+    fits
+}
+
+/// The reference curve that follows the samples most closely.
+pub fn best_fit(samples: &[Sample]) -> Option<Fit> {
+    fits(samples).into_iter().next()
+}
+
+/// Growth exponent b of count ≈ a·nᵇ, the slope of a log-log regression.
+///
+/// None without samples at two or more sizes.
+pub fn exponent(samples: &[Sample]) -> Option<f64> {
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .filter(|s| s.n > 0.0 && s.count > 0.0)
+        .map(|s| (s.n.log10(), s.count.log10()))
+        .collect();
+    let len = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / len;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / len;
+    let var_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let cov: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    (var_x > 0.0).then(|| cov / var_x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::benchmark::measure;
+    use crate::visual_sort::input::Distribution;
+    use crate::visual_sort::{Counters, Sort};
+
+    fn samples(count: impl Fn(f64) -> f64) -> Vec<Sample> {
+        [10.0, 100.0, 1000.0, 10000.0]
+            .into_iter()
+            .map(|n| Sample {
+                n,
+                k: n,
+                count: count(n),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_exact_curves() {
+        for curve in Curve::ALL {
+            let samples = samples(|n| 3.0 * curve.shape(n, n));
+            let fitted = fit(curve, &samples).unwrap();
+            assert!((fitted.scale - 3.0).abs() < 1e-9, "{curve:?}");
+            assert!(fitted.error < 1e-9, "{curve:?}");
+        }
+        let quadratic = samples(|n| 0.5 * n * n);
+        assert_eq!(best_fit(&quadratic).unwrap().curve, Curve::Quadratic);
+        let all = fits(&quadratic);
+        assert_eq!(all.len(), Curve::ALL.len());
+        assert!(all.windows(2).all(|w| w[0].error <= w[1].error));
+        assert!((exponent(&quadratic).unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn needs_two_sizes_for_an_exponent() {
+        assert_eq!(exponent(&[]), None);
+        assert_eq!(exponent(&samples(|n| n)[..1]), None);
+        assert_eq!(fit(Curve::Linear, &samples(|_| 0.0)), None);
+    }
+
+    #[test]
+    fn bubble_grows_quadratic_and_counting_linear() {
+        let measured = |sort: Sort, count: fn(&Counters) -> usize| {
+            [16, 64, 256, 1024]
+                .into_iter()
+                .map(|n| {
+                    let m = measure(sort, Distribution::Shuffled, n, 1);
+                    Sample {
+                        n: n as f64,
+                        k: n as f64,
+                        count: count(&m.counters) as f64,
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let bubble = measured(Sort::Bubble, |c| c.comparisons);
+        assert!((exponent(&bubble).unwrap() - 2.0).abs() < 0.1);
+        assert_eq!(best_fit(&bubble).unwrap().curve, Curve::Quadratic);
+
+        let counting = measured(Sort::Counting, |c| c.updates);
+        assert!((exponent(&counting).unwrap() - 1.0).abs() < 0.1);
+    }
+}
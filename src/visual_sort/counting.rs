@@ -1,12 +1,24 @@
-use super::{Color, SortBase, VisualSort};
-use leptos::*;
+use super::{Aux, AuxBuffer, Color, SortBase, VisualSort};
+
+pub(super) const PSEUDOCODE: [&str; 3] = [
+    "min, max = smallest and largest of a, count = zeros(max - min + 1)",
+    "for v in a: count[v - min] += 1",
+    "for v in min..=max, count[v - min] times: a[x] = v, x = x + 1",
+];
+const MIN_MAX: usize = 0;
+const COUNT: usize = 1;
+const OUTPUT: usize = 2;
 
 pub struct Counting {
     base: SortBase,
+    line: usize,
     count: Vec<usize>,
     counted: bool,
     max: usize,
+    max_i: usize,
     maxed: bool,
+    min: usize,
+    min_i: usize,
     v: usize,
     x: usize,
 }
@@ -15,64 +27,101 @@ impl VisualSort for Counting {
     fn new(base: SortBase) -> Self {
         Self {
             base,
+            line: 0,
             count: Vec::new(),
             counted: false,
             max: 0,
+            max_i: 0,
             maxed: false,
+            min: 0,
+            min_i: 0,
             v: 0,
             x: 0,
         }
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
-        }
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
+    }
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && i == self.x.saturating_sub(1) {
-                if !self.maxed || !self.counted {
-                    Color::Light.as_str()
-                } else {
-                    Color::Green.as_str()
-                }
+    fn color(&self, i: usize) -> Color {
+        if i == self.x.saturating_sub(1) {
+            if !self.maxed || !self.counted {
+                Color::Light
             } else {
-                Color::Red.as_str()
+                Color::Green
             }
-        });
+        } else {
+            Color::Red
+        }
+    }
+
+    fn highlights(&self) -> Vec<usize> {
+        vec![self.x.saturating_sub(1)]
+    }
+
+    fn aux(&self) -> Vec<AuxBuffer> {
+        if !self.maxed {
+            return Vec::new();
+        }
+        let cursor = if self.counted {
+            self.v
+        } else {
+            // value counted last
+            let x = self.x.saturating_sub(1);
+            self.base.scale.key(self.base.data[x]) - self.min
+        };
+        vec![AuxBuffer {
+            aux: Aux::Count,
+            values: self.count.clone(),
+            cursor: Some(cursor),
+        }]
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn aux_len(&self) -> usize {
+        self.count.len()
+    }
+
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
-        // find max value
+        // nothing to read in empty data, which is done from the start
+        if self.base.data.is_empty() {
+            return;
+        }
+
+        // find min and max value
         if !self.maxed {
-            self.base.array_access.update(|n| *n += 1);
-            self.base.array_cmp.update(|n| *n += 1);
-            if self.base.data[self.x] > self.max {
-                self.max = self.base.data[self.x];
-                self.base.set_freq(self.max);
+            self.line = MIN_MAX;
+            if self.x == 0 || self.base.cmp(self.x, self.max_i).is_gt() {
+                self.max_i = self.x;
+            }
+            if self.x == 0 || self.base.cmp(self.x, self.min_i).is_lt() {
+                self.min_i = self.x;
             }
             self.x += 1;
             if self.x < self.base.data.len() {
                 return;
             }
             self.x = 0;
+            self.max = self.base.read_key(self.max_i);
+            self.min = self.base.read_key(self.min_i);
             self.maxed = true;
-            self.count.resize(self.max + 1, 0)
+            self.count.resize(self.max - self.min + 1, 0);
         }
 
-        // count values from 0 to max
+        // count values from min to max
         if !self.counted {
-            self.base.array_access.update(|n| *n += 2);
-            self.count[self.base.data[self.x]] += 1;
-            self.base.set_freq(self.base.data[self.x]);
+            self.line = COUNT;
+            let value = self.base.read_key(self.x) - self.min;
+            self.count[value] += 1;
+            self.base.aux_write(Aux::Count, value, self.count[value]);
             self.x += 1;
             if self.x < self.base.data.len() {
                 return;
@@ -83,17 +132,19 @@ impl VisualSort for Counting {
 
         // update data based on count results
         if self.x < self.base.data.len() {
-            self.base.array_access.update(|n| *n += 1);
-            self.base.array_cmp.update(|n| *n += 1);
-            while self.v < self.count.len() && self.count[self.v] == 0 {
-                self.base.array_access.update(|n| *n += 1);
-                self.base.array_cmp.update(|n| *n += 1);
+            loop {
+                self.base.aux_read(Aux::Count, self.v);
+                if self.count[self.v] > 0 {
+                    break;
+                }
                 self.v += 1;
             }
-            self.base.array_swap.update(|n| *n += 2);
             self.count[self.v] -= 1;
-            self.base.data[self.x] = self.v;
-            self.base.set_freq(self.v);
+            self.base.aux_write(Aux::Count, self.v, self.count[self.v]);
+            // values are rebuilt from their keys, so any tags are lost
+            let value = self.base.scale.untagged(self.v + self.min);
+            self.base.write(self.x, value);
+            self.line = OUTPUT;
             self.x += 1;
             return;
         }
@@ -101,3 +152,17 @@ impl VisualSort for Counting {
         self.base.done = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::run;
+
+    #[test]
+    fn counts_only_the_range_in_use() {
+        let mut counting = Counting::new(SortBase::new(vec![1_000_000, 999_990, 999_995, 999_990]));
+        run(&mut counting);
+        assert_eq!(counting.base.data, [999_990, 999_990, 999_995, 1_000_000]);
+        assert_eq!(counting.count.len(), 11);
+    }
+}
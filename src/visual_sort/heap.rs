@@ -1,8 +1,35 @@
 use super::{Color, SortBase, VisualSort};
-use leptos::*;
+
+pub(super) const PSEUDOCODE: [&str; 6] = [
+    "for x in 0..n: push a[x] onto the heap",
+    "    while it is larger than its parent",
+    "        swap it with its parent",
+    "for end in (0..n).rev(): swap the max a[0] with a[end]",
+    "    while a[i] is smaller than its larger child",
+    "        swap it with the larger child",
+];
+const PUSH: usize = 0;
+const COMPARE_PARENT: usize = 1;
+const SWAP_PARENT: usize = 2;
+const POP: usize = 3;
+const COMPARE_CHILDREN: usize = 4;
+const SWAP_CHILD: usize = 5;
+
+/// The heap region of the data at the current step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HeapView {
+    /// The heap is `data[..len]`.
+    pub len: usize,
+    /// Node being sifted up or down.
+    pub sifting: Option<usize>,
+    /// Whether the heap is still being built, the data after it is then input not yet pushed
+    /// rather than the sorted suffix.
+    pub building: bool,
+}
 
 pub struct Heap {
     base: SortBase,
+    line: usize,
     heap_len: usize,
     heapifying_down: bool,
     heapifying_up: bool,
@@ -14,6 +41,7 @@ impl VisualSort for Heap {
     fn new(base: SortBase) -> Self {
         Self {
             base,
+            line: 0,
             heap_len: 0,
             heapifying_down: false,
             heapifying_up: false,
@@ -22,28 +50,38 @@ impl VisualSort for Heap {
         }
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
+    fn color(&self, i: usize) -> Color {
+        if i == self.y {
+            Color::Light
+        } else if i == self.heap_len {
+            Color::Green
+        } else {
+            Color::Red
         }
+    }
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && i == self.y {
-                Color::Light.as_str()
-            } else if !done && i == self.heap_len {
-                Color::Green.as_str()
-            } else {
-                Color::Red.as_str()
-            }
-        });
+    fn highlights(&self) -> Vec<usize> {
+        vec![self.y, self.heap_len]
+    }
+
+    fn heap(&self) -> Option<HeapView> {
+        Some(HeapView {
+            len: self.heap_len,
+            sifting: (self.y < self.heap_len).then_some(self.y),
+            building: self.x < self.base.data.len(),
+        })
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
@@ -63,17 +101,19 @@ impl VisualSort for Heap {
 
         // insert data to heap
         if self.x < self.base.data.len() {
-            self.base.set_freq(self.base.data[self.x]);
-            self.push(self.base.data[self.x]);
+            let value = self.base.read(self.x);
+            self.push(value);
+            if !self.heapifying_up {
+                self.line = PUSH;
+            }
             self.x += 1;
             return;
         }
 
         // remove max from heap and insert to front of data (back of heap)
         if let Some(v) = self.pop() {
-            self.base.set_freq(v);
-            self.base.array_swap.update(|n| *n += 1);
-            self.base.data[self.heap_len] = v;
+            self.base.write(self.heap_len, v);
+            self.line = POP;
             return;
         }
 
@@ -99,11 +139,10 @@ impl Heap {
 
     fn heap_up(&mut self, i: usize) {
         if let Some(p) = self.parent(i) {
-            self.base.array_access.update(|n| *n += 2);
-            self.base.array_cmp.update(|n| *n += 1);
-            if self.base.data[p] < self.base.data[i] {
-                self.base.array_swap.update(|n| *n += 1);
-                self.base.data.swap(p, i);
+            self.line = COMPARE_PARENT;
+            if self.base.cmp(p, i).is_lt() {
+                self.base.swap(p, i);
+                self.line = SWAP_PARENT;
                 self.heapifying_up = true;
                 self.y = p;
                 return;
@@ -114,34 +153,29 @@ impl Heap {
 
     fn heap_down(&mut self, i: usize) {
         self.heapifying_down = false;
-        let data = &self.base.data;
         let l = self.left_child(i);
         let r = self.right_child(i);
         let mut largest = i;
+        self.line = COMPARE_CHILDREN;
 
-        self.base.array_access.update(|n| *n += 2);
-        self.base.array_cmp.update(|n| *n += 1);
-        if l < self.heap_len && data[l] > data[largest] {
+        if l < self.heap_len && self.base.cmp(l, largest).is_gt() {
             largest = l;
         }
 
-        self.base.array_access.update(|n| *n += 2);
-        self.base.array_cmp.update(|n| *n += 1);
-        if r < self.heap_len && data[r] > data[largest] {
+        if r < self.heap_len && self.base.cmp(r, largest).is_gt() {
             largest = r;
         }
 
         if largest != i {
-            self.base.array_swap.update(|n| *n += 1);
-            self.base.data.swap(i, largest);
+            self.base.swap(i, largest);
+            self.line = SWAP_CHILD;
             self.heapifying_down = true;
             self.y = largest;
         }
     }
 
     fn push(&mut self, value: usize) {
-        self.base.array_swap.update(|n| *n += 1);
-        self.base.data[self.heap_len] = value;
+        self.base.write(self.heap_len, value);
         self.y = self.heap_len;
         self.heap_up(self.heap_len);
         self.heap_len += 1;
@@ -152,13 +186,61 @@ impl Heap {
             return None;
         }
 
-        self.base.array_access.update(|n| *n += 2);
-        self.base.array_swap.update(|n| *n += 1);
-        let value = Some(self.base.data[0]);
+        let value = Some(self.base.read(0));
         self.heap_len -= 1;
-        self.base.data[0] = self.base.data[self.heap_len];
+        let last = self.base.read(self.heap_len);
+        self.base.write(0, last);
         self.y = 0;
         self.heap_down(0);
         value
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::inputs;
+
+    /// Children in the heap that are larger than their parent
+    fn violations(heap: &Heap) -> Vec<usize> {
+        let data = &heap.base.data;
+        (1..heap.heap_len)
+            .filter(|&i| data[heap.parent(i).unwrap()] < data[i])
+            .collect()
+    }
+
+    #[test]
+    fn heap_property_holds_outside_sifted_node() {
+        for input in inputs() {
+            let n = input.len();
+            let mut heap = Heap::new(SortBase::new(input));
+            while !heap.done() {
+                heap.update();
+                for i in violations(&heap) {
+                    if heap.heapifying_up {
+                        assert_eq!(i, heap.y, "only the sifted node can exceed its parent");
+                    } else if heap.heapifying_down {
+                        assert_eq!(heap.parent(i), Some(heap.y), "only children of y");
+                    } else {
+                        panic!("heap violated at {i} in {:?}", heap.base.data);
+                    }
+                }
+
+                let view = heap.heap().unwrap();
+                assert_eq!(view.len, heap.heap_len);
+                assert!(view.sifting.is_none_or(|y| y < view.len));
+
+                // sorted suffix holds the largest items once removing from the heap
+                if !view.building {
+                    assert_eq!(heap.x, n);
+                    let data = &heap.base.data;
+                    let sorted = &data[heap.heap_len..];
+                    assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+                    if let Some(min) = sorted.first() {
+                        assert!(data[..heap.heap_len].iter().all(|v| v <= min));
+                    }
+                }
+            }
+        }
+    }
+}
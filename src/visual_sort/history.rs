@@ -0,0 +1,267 @@
+use super::Op;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+struct Entry {
+    op: Op,
+    // value overwritten by a write, to undo it
+    old: usize,
+}
+
+/// Swaps and writes of a step, with what else it read or compared
+#[derive(Default)]
+struct Step {
+    entries: Vec<Entry>,
+    // runs of indices only looked at, kept small for steps scanning the whole data
+    looked: Vec<Range<usize>>,
+}
+
+/// Operations of a run grouped per step, to move the data back and forth between steps.
+///
+/// Only the latest `limit` steps are kept, older steps are folded into the start data.
+pub struct History {
+    start: Vec<usize>,
+    data: Vec<usize>,
+    steps: VecDeque<Step>,
+    cursor: usize,
+    dropped: usize,
+    limit: usize,
+}
+
+impl History {
+    pub fn new(data: &[usize], limit: usize) -> Self {
+        Self {
+            start: data.to_vec(),
+            data: data.to_vec(),
+            steps: VecDeque::new(),
+            cursor: 0,
+            dropped: 0,
+            limit: limit.max(1),
+        }
+    }
+
+    /// Data as it was after the current step.
+    pub fn data(&self) -> &[usize] {
+        &self.data
+    }
+
+    /// Swaps and writes of the current step.
+    pub fn ops(&self) -> impl Iterator<Item = &Op> {
+        self.step()
+            .into_iter()
+            .flat_map(|step| &step.entries)
+            .map(|entry| &entry.op)
+    }
+
+    /// Indices the current step touched, read and compared as well as changed.
+    pub fn touched(&self) -> impl Iterator<Item = usize> + '_ {
+        let looked = self
+            .step()
+            .into_iter()
+            .flat_map(|step| step.looked.iter().cloned().flatten());
+        self.ops().flat_map(Op::indices).chain(looked)
+    }
+
+    fn step(&self) -> Option<&Step> {
+        self.cursor.checked_sub(1).and_then(|i| self.steps.get(i))
+    }
+
+    pub fn position(&self) -> usize {
+        self.dropped + self.cursor
+    }
+
+    /// Earliest position that can still be reached.
+    pub fn first(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn len(&self) -> usize {
+        self.dropped + self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.cursor == self.steps.len()
+    }
+
+    /// Append the operations of a new step, moving to the end first.
+    ///
+    /// Only swaps and writes are kept as operations, reads and comparisons change nothing
+    /// and are kept as the indices they looked at alone.
+    pub fn record(&mut self, ops: Vec<Op>) {
+        self.seek(self.len());
+        let mut step = Step::default();
+        let mut looked = Vec::new();
+        for op in ops {
+            match op {
+                Op::Swap(..) | Op::Write(..) => {
+                    let old = apply(&mut self.data, op);
+                    step.entries.push(Entry { op, old });
+                }
+                Op::Read(..) | Op::Compare(..) => looked.extend(op.indices()),
+                Op::AuxRead(..) | Op::AuxWrite(..) | Op::AuxCompare(..) => (),
+            }
+        }
+        step.looked = runs(looked);
+        self.steps.push_back(step);
+        self.cursor += 1;
+
+        if self.steps.len() > self.limit {
+            if let Some(step) = self.steps.pop_front() {
+                for entry in step.entries {
+                    apply(&mut self.start, entry.op);
+                }
+                self.cursor -= 1;
+                self.dropped += 1;
+            }
+        }
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        for entry in self.steps[self.cursor].entries.iter().rev() {
+            match entry.op {
+                Op::Swap(i, j) => self.data.swap(i, j),
+                Op::Write(i, _) => self.data[i] = entry.old,
+                _ => (),
+            }
+        }
+        true
+    }
+
+    pub fn forward(&mut self) -> bool {
+        if self.at_end() {
+            return false;
+        }
+        for entry in &self.steps[self.cursor].entries {
+            apply(&mut self.data, entry.op);
+        }
+        self.cursor += 1;
+        true
+    }
+
+    /// Move to a position, clamped to the steps still kept.
+    pub fn seek(&mut self, position: usize) {
+        let target = position.clamp(self.first(), self.len()) - self.dropped;
+
+        // replay from the start when that is closer than stepping back
+        if target < self.cursor && target < self.cursor - target {
+            self.data.clone_from(&self.start);
+            self.cursor = 0;
+        }
+        while self.cursor > target {
+            self.back();
+        }
+        while self.cursor < target {
+            self.forward();
+        }
+    }
+}
+
+/// Apply the data change of an operation, returning the value it overwrote
+fn apply(data: &mut [usize], op: Op) -> usize {
+    match op {
+        Op::Swap(i, j) => {
+            data.swap(i, j);
+            data[i]
+        }
+        Op::Write(i, value) => std::mem::replace(&mut data[i], value),
+        _ => 0,
+    }
+}
+
+/// Indices gathered into as few runs as cover them
+fn runs(mut indices: Vec<usize>) -> Vec<Range<usize>> {
+    indices.sort_unstable();
+    indices.dedup();
+    let mut runs: Vec<Range<usize>> = Vec::new();
+    for i in indices {
+        match runs.last_mut() {
+            Some(run) if run.end == i => run.end += 1,
+            _ => runs.push(i..i + 1),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::{input, tests::inputs, Sort, SortBase};
+
+    /// Record every step of a sort, with the data after each step
+    fn record(sort: Sort, input: &[usize], limit: usize) -> (History, Vec<Vec<usize>>) {
+        let mut sorter = sort.init(SortBase::new(input.to_vec()));
+        let mut history = History::new(input, limit);
+        let mut snapshots = vec![input.to_vec()];
+        while !sorter.done() {
+            sorter.update();
+            history.record(sorter.base_mut().take_ops());
+            snapshots.push(sorter.base().data().to_vec());
+        }
+        (history, snapshots)
+    }
+
+    #[test]
+    fn steps_back_through_every_snapshot() {
+        for sort in Sort::ALL {
+            for input in inputs().iter().step_by(5) {
+                let (mut history, snapshots) = record(sort, input, usize::MAX);
+                assert_eq!(history.len(), snapshots.len() - 1);
+                for snapshot in snapshots.iter().rev() {
+                    assert_eq!(history.data(), snapshot, "{}", sort.name_as_str());
+                    history.back();
+                }
+                assert!(!history.back());
+            }
+        }
+    }
+
+    #[test]
+    fn seeks_to_any_position() {
+        let input = input::shuffled(30, 0);
+        for sort in Sort::ALL {
+            let (mut history, snapshots) = record(sort, &input, usize::MAX);
+            for position in [3, 0, snapshots.len() / 2, snapshots.len() - 1, 1] {
+                history.seek(position);
+                assert_eq!(history.position(), position);
+                assert_eq!(history.data(), snapshots[position]);
+            }
+        }
+    }
+
+    #[test]
+    fn steps_touch_what_they_looked_at() {
+        let mut history = History::new(&[3, 1, 2, 0], 10);
+        let reads = (0..4).map(Op::Read);
+        history.record(reads.chain([Op::Compare(0, 2), Op::Swap(3, 1)]).collect());
+        assert_eq!(history.ops().collect::<Vec<_>>(), [&Op::Swap(3, 1)]);
+        // a scan of the whole data is kept as one run
+        assert_eq!(history.steps[0].looked.len(), 1);
+        let mut touched: Vec<usize> = history.touched().collect();
+        touched.sort_unstable();
+        touched.dedup();
+        assert_eq!(touched, [0, 1, 2, 3]);
+        assert_eq!(runs(vec![5, 1, 2, 9, 1]), [1..3, 5..6, 9..10]);
+    }
+
+    #[test]
+    fn keeps_only_limit_steps() {
+        let input: Vec<usize> = (1..=30).rev().collect();
+        let (mut history, snapshots) = record(Sort::Bubble, &input, 10);
+        let last = snapshots.len() - 1;
+        assert_eq!(history.first(), last - 10);
+        history.seek(0);
+        assert_eq!(history.position(), last - 10);
+        assert_eq!(history.data(), snapshots[last - 10]);
+        history.seek(last);
+        assert!(history.at_end());
+        assert_eq!(history.data(), snapshots[last]);
+    }
+}
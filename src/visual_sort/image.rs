@@ -0,0 +1,349 @@
+use super::bins::Bins;
+use super::{Sort, SortBase, StepCount, VisualSort};
+
+/// An RGB image, pixels row by row from the top left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    /// Pixels with an opaque alpha channel, as canvas image data takes them.
+    pub fn rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&[r, g, b]| [r, g, b, 255])
+            .collect()
+    }
+
+    /// Encode as a PNG file, left uncompressed.
+    pub fn png(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, default compression, filter and no interlace
+        header.extend([8, 2, 0, 0, 0]);
+
+        // every row starts with its filter type, none
+        let mut raw = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks(self.width.max(1)) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+/// Color of a value on a hue scale from red to magenta, by where it lies in the data.
+pub fn hue(fraction: f64) -> [u8; 3] {
+    // hsl(fraction * 300, 75%, 55%), stopping short of a full turn so the ends differ
+    let (saturation, lightness) = (0.75, 0.55);
+    let h = fraction.clamp(0.0, 1.0) * 300.0 / 60.0;
+    let c = (1.0 - (2.0 * lightness - 1.0f64).abs()) * saturation;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as usize {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = lightness - c / 2.0;
+    [r, g, b].map(|v| ((v + m) * 255.0).round() as u8)
+}
+
+/// Run a sort to the end, taking a snapshot of the data every so many steps.
+///
+/// Each snapshot becomes a row of pixels colored by value, first the input and last the
+/// sorted data, with at most `rows` rows spread evenly over the run. Items are widened to
+/// fill about `width` pixels, or binned by their average when there are more of them.
+pub fn history(sort: Sort, base: SortBase, rows: usize, width: usize) -> Image {
+    let mut snapshots = Snapshots::new(sort, base, rows, width);
+    while !snapshots.work(usize::MAX) {}
+    snapshots.image()
+}
+
+/// A [`history`] image taken a budget of steps at a time, so a page can work on it a
+/// little each frame.
+///
+/// The run is stepped through twice, first to count its steps, then to take the rows.
+pub struct Snapshots {
+    sort: Sort,
+    base: Option<SortBase>,
+    counting: StepCount,
+    sorter: Option<Box<dyn VisualSort>>,
+    steps: usize,
+    step: usize,
+    rows: usize,
+    width: usize,
+    taken: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Snapshots {
+    pub fn new(sort: Sort, base: SortBase, rows: usize, width: usize) -> Self {
+        Self {
+            sort,
+            counting: StepCount::new(sort, base.clone()),
+            base: Some(base),
+            sorter: None,
+            steps: 0,
+            step: 0,
+            rows,
+            width: width.max(1),
+            taken: 0,
+            pixels: Vec::new(),
+        }
+    }
+
+    /// How far the rows are taken, from 0 to 1, or None while steps are still counted.
+    pub fn progress(&self) -> Option<f64> {
+        self.sorter
+            .as_ref()
+            .map(|_| self.taken as f64 / self.rows as f64)
+    }
+
+    /// Step up to budget times, true once every row is taken.
+    ///
+    /// A row costs as much of the budget as it has items, since each is looked at.
+    pub fn work(&mut self, mut budget: usize) -> bool {
+        let Some(sorter) = self.sorter.as_mut() else {
+            if let Some(steps) = self.counting.work(budget) {
+                self.steps = steps;
+                self.rows = self.rows.clamp(2, steps.max(1) + 1);
+                self.sorter = self.base.take().map(|base| self.sort.init(base));
+            }
+            return false;
+        };
+        let items = sorter.base().data().len();
+        while self.taken < self.rows {
+            // the first row is the input, the last the sorted data
+            let target = self.taken * self.steps / (self.rows - 1).max(1);
+            while self.step < target && budget > 0 {
+                sorter.update();
+                sorter.base_mut().take_ops();
+                self.step += 1;
+                budget -= 1;
+            }
+            if self.step < target {
+                return false;
+            }
+            self.pixels.extend(row(sorter.base(), self.width));
+            self.taken += 1;
+            budget = budget.saturating_sub(items);
+        }
+        true
+    }
+
+    /// The image of the rows taken.
+    pub fn image(self) -> Image {
+        let height = self.taken;
+        Image {
+            width: self.pixels.len() / height.max(1),
+            height,
+            pixels: self.pixels,
+        }
+    }
+}
+
+/// Pixels of the data as it is, widened or binned to about width
+fn row(base: &SortBase, width: usize) -> Vec<[u8; 3]> {
+    let (data, scale) = (base.data(), base.scale());
+    let items = data.len();
+    if items > width {
+        let bins = Bins::new(data, width);
+        (0..bins.len())
+            .map(|column| hue(scale.fraction(bins.get(column).avg())))
+            .collect()
+    } else {
+        data.iter()
+            .flat_map(|&value| std::iter::repeat_n(hue(scale.fraction(value)), width / items))
+            .collect()
+    }
+}
+
+/// Append a chunk of the given type, with its length and checksum
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Wrap data in a zlib stream of stored deflate blocks, no compression
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // deflate, 32K window, no preset dictionary, fastest
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::{Sort, SortBase};
+
+    /// Undo zlib_stored, checking each block on the way
+    fn unstore(zlib: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut at = 2;
+        loop {
+            let last = zlib[at] == 1;
+            let len = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]);
+            let nlen = u16::from_le_bytes([zlib[at + 3], zlib[at + 4]]);
+            assert_eq!(len, !nlen);
+            data.extend(&zlib[at + 5..at + 5 + len as usize]);
+            at += 5 + len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(zlib[at..], adler32(&data).to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn png_holds_the_rows() {
+        let image = Image {
+            width: 2,
+            height: 2,
+            pixels: vec![[1, 2, 3], [4, 5, 6], [7, 8, 9], [10, 11, 12]],
+        };
+        let png = image.png();
+        assert_eq!(
+            png[..8],
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']
+        );
+        // IHDR first, 13 bytes of width, height and format
+        assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(png[16..24], [0, 0, 0, 2, 0, 0, 0, 2]);
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(png[37..41], *b"IDAT");
+        let raw = unstore(&png[41..41 + idat_len]);
+        assert_eq!(raw, [0, 1, 2, 3, 4, 5, 6, 0, 7, 8, 9, 10, 11, 12]);
+
+        // rows too large for one stored block
+        let wide = vec![7; 70_000];
+        assert_eq!(unstore(&zlib_stored(&wide)), wide);
+    }
+
+    #[test]
+    fn history_runs_from_input_to_sorted() {
+        for sort in Sort::ALL {
+            let input = vec![5, 1, 4, 2, 3, 2];
+            let base = SortBase::new(input.clone());
+            let scale = base.scale();
+            let image = history(sort, base, 8, 12);
+            assert_eq!((image.width, image.height), (12, 8), "{sort:?}");
+            let colors = |data: &[usize]| -> Vec<[u8; 3]> {
+                data.iter()
+                    .flat_map(|&v| [hue(scale.fraction(v)); 2])
+                    .collect()
+            };
+            assert_eq!(image.pixels[..12], colors(&input), "{sort:?}");
+            assert_eq!(
+                image.pixels[7 * 12..],
+                colors(&[1, 2, 2, 3, 4, 5]),
+                "{sort:?}"
+            );
+        }
+        // short runs keep every step
+        let image = history(Sort::Insertion, SortBase::new(vec![1, 2]), 100, 2);
+        assert!(image.height < 100);
+        // and empty data has nothing to show
+        let image = history(Sort::Quick, SortBase::new(Vec::new()), 100, 2);
+        assert!(image.pixels.is_empty());
+        // many items are binned to the width
+        let image = history(
+            Sort::Radix,
+            SortBase::new((0..1000).rev().collect()),
+            10,
+            100,
+        );
+        assert_eq!((image.width, image.height), (100, 10));
+    }
+
+    #[test]
+    fn snapshots_in_small_budgets_match_the_whole_history() {
+        let input: Vec<usize> = (0..40).rev().collect();
+        for sort in Sort::ALL {
+            let whole = history(sort, SortBase::new(input.clone()), 16, 80);
+            let mut snapshots = Snapshots::new(sort, SortBase::new(input.clone()), 16, 80);
+            assert_eq!(snapshots.progress(), None);
+            let mut progress = 0.0;
+            while !snapshots.work(50) {
+                if let Some(now) = snapshots.progress() {
+                    assert!(now >= progress && now < 1.0, "{sort:?}");
+                    progress = now;
+                }
+            }
+            assert_eq!(snapshots.progress(), Some(1.0));
+            assert_eq!(snapshots.image(), whole, "{sort:?}");
+        }
+    }
+
+    #[test]
+    fn hue_runs_from_red_to_magenta() {
+        assert_eq!(hue(0.0), [226, 54, 54]);
+        assert_eq!(hue(1.0), [226, 54, 226]);
+        assert_ne!(hue(0.5), hue(0.6));
+    }
+}
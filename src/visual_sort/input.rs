@@ -0,0 +1,380 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+/// Shape of the generated input, values are within 1 to items except for sparse input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    Shuffled,
+    Sorted,
+    Reversed,
+    NearlySorted,
+    FewUnique,
+    Sawtooth,
+    OrganPipe,
+    Gaussian,
+    ShuffledTail,
+    Sparse,
+}
+
+impl Distribution {
+    pub const ALL: [Distribution; 10] = [
+        Self::Shuffled,
+        Self::Sorted,
+        Self::Reversed,
+        Self::NearlySorted,
+        Self::FewUnique,
+        Self::Sawtooth,
+        Self::OrganPipe,
+        Self::Gaussian,
+        Self::ShuffledTail,
+        Self::Sparse,
+    ];
+
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Shuffled => "Shuffled",
+            Self::Sorted => "Sorted",
+            Self::Reversed => "Reversed",
+            Self::NearlySorted => "Nearly sorted",
+            Self::FewUnique => "Few unique",
+            Self::Sawtooth => "Sawtooth",
+            Self::OrganPipe => "Organ pipe",
+            Self::Gaussian => "Gaussian",
+            Self::ShuffledTail => "Sorted, shuffled tail",
+            Self::Sparse => "Sparse, up to 1,000,000",
+        }
+    }
+
+    /// Short name used in links.
+    pub fn key_as_str(&self) -> &'static str {
+        match self {
+            Self::Shuffled => "shuffled",
+            Self::Sorted => "sorted",
+            Self::Reversed => "reversed",
+            Self::NearlySorted => "nearly-sorted",
+            Self::FewUnique => "few-unique",
+            Self::Sawtooth => "sawtooth",
+            Self::OrganPipe => "organ-pipe",
+            Self::Gaussian => "gaussian",
+            Self::ShuffledTail => "shuffled-tail",
+            Self::Sparse => "sparse",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.key_as_str() == key)
+    }
+
+    pub fn generate(&self, items: usize, seed: u64) -> Vec<usize> {
+        let mut rng = rng(seed);
+        let sorted = (1..=items).collect::<Vec<usize>>();
+        match self {
+            Self::Shuffled => shuffled(items, seed),
+            Self::Sorted => sorted,
+            Self::Reversed => sorted.into_iter().rev().collect(),
+            Self::NearlySorted => {
+                // a few random swaps, about one per 20 items
+                let mut data = sorted;
+                for _ in 0..(items / 20).max(1) {
+                    let i = rng.gen_range(0..items);
+                    let j = rng.gen_range(0..items);
+                    data.swap(i, j);
+                }
+                data
+            }
+            Self::FewUnique => {
+                let levels: Vec<usize> = (1..=5).map(|k| (k * items / 5).max(1)).collect();
+                (0..items)
+                    .map(|_| *levels.choose(&mut rng).unwrap())
+                    .collect()
+            }
+            Self::Sawtooth => {
+                let tooth = items.div_ceil(4);
+                (0..items)
+                    .map(|i| (i % tooth) * items / tooth + 1)
+                    .collect()
+            }
+            Self::OrganPipe => {
+                let up = sorted.iter().step_by(2);
+                let down = sorted.iter().skip(1).step_by(2).rev();
+                up.chain(down).copied().collect()
+            }
+            Self::Gaussian => {
+                // Box-Muller transform, centered with most values within the range
+                let mean = (items as f64 + 1.0) / 2.0;
+                let deviation = items as f64 / 6.0;
+                (0..items)
+                    .map(|_| {
+                        let u1: f64 = 1.0 - rng.gen::<f64>();
+                        let u2: f64 = rng.gen();
+                        let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+                        let value = (mean + z * deviation).round() as usize;
+                        value.clamp(1, items)
+                    })
+                    .collect()
+            }
+            Self::ShuffledTail => {
+                // sorted, followed by a tenth of the items picked at random
+                let mut tail = shuffled(items, seed);
+                let mut data = tail.split_off((items / 10).max(1).min(items));
+                data.sort_unstable();
+                data.append(&mut tail);
+                data
+            }
+            Self::Sparse => (0..items).map(|_| rng.gen_range(1..=SPARSE_MAX)).collect(),
+        }
+    }
+}
+
+const SPARSE_MAX: usize = 1_000_000;
+
+/// Largest value accepted in custom input, counting sort needs a slot per value in the range.
+pub const CUSTOM_MAX: usize = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    NotANumber { position: usize, token: String },
+    Negative { position: usize, token: String },
+    TooLarge { position: usize, token: String },
+    TooMany { count: usize, max_items: usize },
+    UnclosedArray,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "No numbers found"),
+            Self::NotANumber { position, token } => {
+                write!(f, "Item {position}, \"{token}\", is not a whole number")
+            }
+            Self::Negative { position, token } => {
+                write!(
+                    f,
+                    "Item {position}, {token}, is negative, only 0 and up is supported"
+                )
+            }
+            Self::TooLarge { position, token } => {
+                write!(f, "Item {position}, {token}, is larger than {CUSTOM_MAX}")
+            }
+            Self::TooMany { count, max_items } => {
+                write!(
+                    f,
+                    "Found {count} numbers, at most {max_items} are supported"
+                )
+            }
+            Self::UnclosedArray => write!(f, "JSON array is missing the closing ]"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Read numbers separated by commas, semicolons or whitespace, as a list, CSV or a JSON array.
+///
+/// A first line without any numbers, like a CSV header, is skipped.
+pub fn parse(text: &str, max_items: usize) -> Result<Vec<usize>, ParseError> {
+    let mut text = text.trim();
+    if let Some(array) = text.strip_prefix('[') {
+        text = array.strip_suffix(']').ok_or(ParseError::UnclosedArray)?;
+    }
+
+    let is_separator = |c: char| c == ',' || c == ';' || c.is_whitespace();
+    let mut lines = text.lines().peekable();
+    if let Some(header) = lines.peek() {
+        let has_number = header
+            .split(is_separator)
+            .any(|t| is_whole_number(t.trim_matches('"')));
+        if !has_number && text.lines().nth(1).is_some() {
+            lines.next();
+        }
+    }
+
+    let mut data = Vec::new();
+    let mut items = lines
+        .flat_map(|line| line.split(is_separator).filter(|t| !t.is_empty()))
+        .enumerate();
+    while let Some((i, token)) = items.next() {
+        let position = i + 1;
+        if data.len() == max_items {
+            // the rest is only counted, not kept
+            let count = position + items.count();
+            return Err(ParseError::TooMany { count, max_items });
+        }
+        let token = token.trim_matches('"');
+        let value = match token.parse::<i128>() {
+            Ok(value) => value,
+            // too many digits for any value
+            Err(_) if is_whole_number(token) && token.starts_with('-') => i128::MIN,
+            Err(_) if is_whole_number(token) => i128::MAX,
+            Err(_) => {
+                let token = token.to_string();
+                return Err(ParseError::NotANumber { position, token });
+            }
+        };
+        if value < 0 {
+            let token = token.to_string();
+            return Err(ParseError::Negative { position, token });
+        }
+        if value > CUSTOM_MAX as i128 {
+            let token = token.to_string();
+            return Err(ParseError::TooLarge { position, token });
+        }
+        data.push(value as usize);
+    }
+
+    if data.is_empty() {
+        return Err(ParseError::Empty);
+    }
+    Ok(data)
+}
+
+/// Whether a token is written as a whole number, however large
+fn is_whole_number(token: &str) -> bool {
+    let digits = token.strip_prefix(['-', '+']).unwrap_or(token);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Seed for a new run, small enough to read and share.
+pub fn random_seed() -> u64 {
+    rand::random::<u32>() as u64
+}
+
+/// Random generator every input is built from, the same seed always gives the same input.
+pub fn rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// Numbers 1 to items in a random order decided by the seed.
+pub fn shuffled(items: usize, seed: u64) -> Vec<usize> {
+    let mut data: Vec<usize> = (1..=items).collect();
+    data.shuffle(&mut rng(seed));
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_input() {
+        for distribution in Distribution::ALL {
+            assert_eq!(
+                distribution.generate(100, 42),
+                distribution.generate(100, 42)
+            );
+        }
+        assert_ne!(shuffled(100, 42), shuffled(100, 43));
+    }
+
+    #[test]
+    fn values_within_items() {
+        for distribution in Distribution::ALL {
+            let max = match distribution {
+                Distribution::Sparse => |_| SPARSE_MAX,
+                _ => |items| items,
+            };
+            for items in 1..=100 {
+                let data = distribution.generate(items, 7);
+                assert_eq!(data.len(), items, "{}", distribution.name_as_str());
+                assert!(data.iter().all(|v| (1..=max(items)).contains(v)));
+            }
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        use Distribution::*;
+        assert_eq!(Reversed.generate(4, 0), [4, 3, 2, 1]);
+        assert_eq!(Sawtooth.generate(8, 0), [1, 5, 1, 5, 1, 5, 1, 5]);
+        assert_eq!(OrganPipe.generate(6, 0), [1, 3, 5, 6, 4, 2]);
+        let tail = ShuffledTail.generate(100, 0);
+        assert!(tail[..90].windows(2).all(|w| w[0] < w[1]));
+        for distribution in [Shuffled, NearlySorted, OrganPipe, ShuffledTail] {
+            let mut data = distribution.generate(100, 3);
+            data.sort_unstable();
+            assert_eq!(
+                data,
+                Sorted.generate(100, 3),
+                "{}",
+                distribution.name_as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn parses_lists_csv_and_json() {
+        let textbook = vec![5, 2, 4, 6, 1, 3];
+        assert_eq!(parse("5, 2, 4, 6, 1, 3", 10), Ok(textbook.clone()));
+        assert_eq!(parse("5 2 4\n6 1 3\n", 10), Ok(textbook.clone()));
+        assert_eq!(parse("[5,2,4,6,1,3]", 10), Ok(textbook.clone()));
+        assert_eq!(parse("value\n5\n2\n4\n6\n1\n3", 10), Ok(textbook.clone()));
+        assert_eq!(parse("\"5\";\"2\";4;6;1;3", 10), Ok(textbook));
+        assert_eq!(parse("0, 1000000", 10), Ok(vec![0, 1_000_000]));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert_eq!(parse("  ", 10), Err(ParseError::Empty));
+        assert_eq!(parse("[]", 10), Err(ParseError::Empty));
+        assert_eq!(parse("[1, 2", 10), Err(ParseError::UnclosedArray));
+        let token = "2.5".to_string();
+        assert_eq!(
+            parse("1, 2.5", 10),
+            Err(ParseError::NotANumber { position: 2, token })
+        );
+        let token = "-3".to_string();
+        assert_eq!(
+            parse("1 2 -3", 10),
+            Err(ParseError::Negative { position: 3, token })
+        );
+        let token = "1000001".to_string();
+        assert_eq!(
+            parse("1000001", 10),
+            Err(ParseError::TooLarge { position: 1, token })
+        );
+        assert_eq!(
+            parse("1 2 3", 2),
+            Err(ParseError::TooMany {
+                count: 3,
+                max_items: 2
+            })
+        );
+        // counted past the limit, even where the rest would not parse
+        assert_eq!(
+            parse("1 2 3 x -4", 2),
+            Err(ParseError::TooMany {
+                count: 5,
+                max_items: 2
+            })
+        );
+        // numbers too large for any type are no header
+        let token = "99999999999999999999".to_string();
+        assert_eq!(
+            parse("99999999999999999999\n1 2", 10),
+            Err(ParseError::TooLarge { position: 1, token })
+        );
+        let token = "1".repeat(50);
+        assert_eq!(
+            parse(&format!("{token} 1"), 10),
+            Err(ParseError::TooLarge { position: 1, token })
+        );
+        let token = format!("-{}", "1".repeat(50));
+        assert_eq!(
+            parse(&token, 10),
+            Err(ParseError::Negative { position: 1, token })
+        );
+    }
+
+    #[test]
+    fn key_round_trip() {
+        for distribution in Distribution::ALL {
+            assert_eq!(
+                Distribution::from_key(distribution.key_as_str()),
+                Some(distribution)
+            );
+        }
+    }
+}
@@ -1,8 +1,15 @@
 use super::{Color, SortBase, VisualSort};
-use leptos::*;
+
+pub(super) const PSEUDOCODE: [&str; 2] = [
+    "for x in 1..n, y = x: while y > 0 and a[y] < a[y - 1]",
+    "    swap a[y], a[y - 1], y = y - 1",
+];
+const COMPARE: usize = 0;
+const SWAP: usize = 1;
 
 pub struct Insertion {
     base: SortBase,
+    line: usize,
     x: usize,
     y: usize,
     inserting: bool,
@@ -12,67 +19,64 @@ impl VisualSort for Insertion {
     fn new(base: SortBase) -> Self {
         Self {
             base,
+            line: 0,
             x: 1,
             y: 0,
             inserting: false,
         }
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
+    fn color(&self, i: usize) -> Color {
+        if self.inserting && self.y == i {
+            Color::Light
+        } else if self.x == i {
+            Color::Green
+        } else {
+            Color::Red
         }
+    }
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && self.inserting && self.y == i {
-                Color::Light.as_str()
-            } else if !done && self.x - 1 == i {
-                Color::Green.as_str()
-            } else {
-                Color::Red.as_str()
-            }
-        });
+    fn highlights(&self) -> Vec<usize> {
+        vec![self.y, self.x]
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
-        if self.inserting {
-            if self.y > 0 {
-                let i = self.y - 1;
-                self.base.array_access.update(|n| *n += 2);
-                self.base.array_cmp.update(|n| *n += 1);
-                if self.base.data[self.y] < self.base.data[i] {
-                    self.base.array_swap.update(|n| *n += 1);
-                    self.base.data.swap(self.y, i);
-                    self.y = i;
-                    return;
-                }
+        if !self.inserting {
+            if self.x >= self.base.data.len() {
+                self.base.done = true;
+                return;
             }
-            self.inserting = false;
-        };
+            self.y = self.x;
+            self.inserting = true;
+        }
 
-        for x in self.x..self.base.data.len() {
-            self.x = x;
-            let i = x - 1;
-            self.base.array_access.update(|n| *n += 2);
-            self.base.array_cmp.update(|n| *n += 1);
-            if self.base.data[x] < self.base.data[i] {
-                self.base.array_swap.update(|n| *n += 1);
-                self.base.data.swap(x, i);
-                self.base.set_freq(self.base.data[i]);
-                self.x = x + 1;
-                self.inserting = true;
-                self.y = i;
+        // compare with the one before each step, swapping down while smaller
+        let i = self.y - 1;
+        self.line = COMPARE;
+        if self.base.cmp(self.y, i).is_lt() {
+            self.base.swap(self.y, i);
+            self.line = SWAP;
+            self.y = i;
+            if self.y > 0 {
                 return;
             }
         }
-        self.base.done = true;
+
+        // in place, go on with the next
+        self.inserting = false;
+        self.x += 1;
+        self.base.done = self.x >= self.base.data.len();
     }
 }
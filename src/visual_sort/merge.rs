@@ -1,33 +1,52 @@
-use super::{Color, SortBase, VisualSort};
-use leptos::*;
+use super::{Aux, AuxBuffer, Color, Frame, SortBase, VisualSort};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+type Buffer = Rc<RefCell<Vec<usize>>>;
+
 #[derive(Clone)]
 struct MergeState {
-    arr: Rc<RefCell<Vec<usize>>>,
-    arr_l: Rc<RefCell<Vec<usize>>>,
-    arr_r: Rc<RefCell<Vec<usize>>>,
+    arr: Buffer,
+    arr_l: Buffer,
+    arr_r: Buffer,
     l: usize,
     r: usize,
     s: usize,
     sorted: bool,
     start_i: usize,
+    depth: usize,
 }
 
+pub(super) const PSEUDOCODE: [&str; 3] = [
+    "merge_sort(a), len(a) > 1: left, right = copies of each half, merge_sort both",
+    "    while left and right both have values: a[x] = the smaller front value, from left on ties",
+    "    a[x] = each value left in left or right",
+];
+const SPLIT: usize = 0;
+const TAKE_SMALLER: usize = 1;
+const TAKE_REST: usize = 2;
+
 pub struct Merge {
     base: SortBase,
+    line: usize,
     stack: Vec<MergeState>,
 }
 
 impl VisualSort for Merge {
-    fn new(base: SortBase) -> Self {
+    fn new(mut base: SortBase) -> Self {
+        // empty data has no call to make
+        if base.data.is_empty() {
+            return Self {
+                base,
+                line: 0,
+                stack: Vec::new(),
+            };
+        }
         let arr = Rc::new(RefCell::new(base.data.clone()));
-        let mid = arr.borrow().len() / 2;
-        let arr_l = Rc::new(RefCell::new(arr.borrow()[..mid].to_owned()));
-        let arr_r = Rc::new(RefCell::new(arr.borrow()[mid..].to_owned()));
+        let (arr_l, arr_r) = split(&mut base, &arr);
         Self {
             base,
+            line: 0,
             stack: vec![MergeState {
                 arr,
                 arr_l,
@@ -37,38 +56,92 @@ impl VisualSort for Merge {
                 s: 0,
                 sorted: false,
                 start_i: 0,
+                depth: 0,
             }],
         }
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
-        }
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
+    }
 
+    fn color(&self, i: usize) -> Color {
         match self.stack.last() {
-            None => self.base.draw(|_done: bool, _i: usize| Color::Red.as_str()),
-            Some(state) => self.base.draw(move |done: bool, i: usize| {
-                if !done && i == (state.start_i + state.s).saturating_sub(1) {
-                    Color::Light.as_str()
-                } else if !done
-                    && (i == state.start_i
-                        || i == state.start_i + state.arr.borrow().len().saturating_sub(1))
+            None => Color::Red,
+            Some(state) => {
+                if i == (state.start_i + state.s).saturating_sub(1) {
+                    Color::Light
+                } else if i == state.start_i
+                    || i == state.start_i + state.arr.borrow().len().saturating_sub(1)
                 {
-                    Color::Green.as_str()
+                    Color::Green
                 } else {
-                    Color::Red.as_str()
+                    Color::Red
                 }
-            }),
+            }
         }
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn highlights(&self) -> Vec<usize> {
+        self.stack.last().map_or(Vec::new(), |state| {
+            let end = state.start_i + state.arr.borrow().len().saturating_sub(1);
+            vec![
+                (state.start_i + state.s).saturating_sub(1),
+                state.start_i,
+                end,
+            ]
+        })
+    }
+
+    fn aux(&self) -> Vec<AuxBuffer> {
+        let Some(state) = self.stack.last() else {
+            return Vec::new();
+        };
+        vec![
+            AuxBuffer {
+                aux: Aux::Left,
+                values: state.arr_l.borrow().clone(),
+                cursor: Some(state.l),
+            },
+            AuxBuffer {
+                aux: Aux::Right,
+                values: state.arr_r.borrow().clone(),
+                cursor: Some(state.r),
+            },
+        ]
+    }
+
+    fn aux_len(&self) -> usize {
+        // each state holds its halves, its whole is a half of the state below it
+        let root = self
+            .stack
+            .first()
+            .map_or(0, |state| state.arr.borrow().len());
+        let halves: usize = self
+            .stack
+            .iter()
+            .map(|state| state.arr_l.borrow().len() + state.arr_r.borrow().len())
+            .sum();
+        root + halves
+    }
+
+    fn frames(&self) -> Vec<Frame> {
+        self.stack
+            .iter()
+            .map(|state| Frame {
+                start: state.start_i,
+                end: state.start_i + state.arr.borrow().len(),
+                depth: state.depth,
+            })
+            .collect()
+    }
+
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
@@ -79,11 +152,16 @@ impl VisualSort for Merge {
 
             if !state.sorted {
                 let (left, right) = self.split_state(&state);
+                self.line = SPLIT;
                 state.sorted = true;
                 self.stack.push(state);
-                self.stack.push(right);
-                self.stack.push(left);
-                continue;
+                // halves of one item are sorted already
+                for half in [right, left] {
+                    if half.arr.borrow().len() > 1 {
+                        self.stack.push(half);
+                    }
+                }
+                return;
             }
 
             // scope for refcell borrow
@@ -92,10 +170,11 @@ impl VisualSort for Merge {
                 let arr_l = state.arr_l.borrow();
                 let arr_r = state.arr_r.borrow();
                 if state.l < arr_l.len() && state.r < arr_r.len() {
-                    self.base.array_access.update(|n| *n += 2);
-                    self.base.array_cmp.update(|n| *n += 1);
-                    self.base.array_swap.update(|n| *n += 1);
-                    if arr_l[state.l] < arr_r[state.r] {
+                    self.base.aux_cmp(Aux::Left, state.l, Aux::Right, state.r);
+                    self.line = TAKE_SMALLER;
+                    // take from the left on ties to keep equal keys in order
+                    let scale = self.base.scale;
+                    if scale.key(arr_l[state.l]) <= scale.key(arr_r[state.r]) {
                         arr[state.l + state.r] = arr_l[state.l];
                         state.l += 1;
                     } else {
@@ -103,22 +182,21 @@ impl VisualSort for Merge {
                         state.r += 1;
                     }
                 } else if state.l < arr_l.len() {
-                    self.base.array_swap.update(|n| *n += 1);
+                    self.base.aux_read(Aux::Left, state.l);
+                    self.line = TAKE_REST;
                     arr[state.l + state.r] = arr_l[state.l];
                     state.l += 1;
                 } else if state.r < arr_r.len() {
-                    self.base.array_swap.update(|n| *n += 1);
+                    self.base.aux_read(Aux::Right, state.r);
+                    self.line = TAKE_REST;
                     arr[state.l + state.r] = arr_r[state.r];
                     state.r += 1;
                 }
             }
 
             if state.s < state.arr.borrow().len() {
-                self.base.array_access.update(|n| *n += 1);
-                self.base.array_swap.update(|n| *n += 1);
                 let value = state.arr.borrow()[state.s];
-                self.base.set_freq(value);
-                self.base.data[state.start_i + state.s] = value;
+                self.base.write(state.start_i + state.s, value);
                 state.s += 1;
                 self.stack.push(state);
                 return;
@@ -129,11 +207,9 @@ impl VisualSort for Merge {
 }
 
 impl Merge {
-    fn split_state(&self, state: &MergeState) -> (MergeState, MergeState) {
+    fn split_state(&mut self, state: &MergeState) -> (MergeState, MergeState) {
         let arr = state.arr_l.clone();
-        let mid = arr.borrow().len() / 2;
-        let arr_l = Rc::new(RefCell::new(arr.borrow()[..mid].to_owned()));
-        let arr_r = Rc::new(RefCell::new(arr.borrow()[mid..].to_owned()));
+        let (arr_l, arr_r) = split(&mut self.base, &arr);
         let left = MergeState {
             arr,
             arr_l,
@@ -143,12 +219,11 @@ impl Merge {
             s: 0,
             sorted: false,
             start_i: state.start_i,
+            depth: state.depth + 1,
         };
 
         let arr = state.arr_r.clone();
-        let mid = arr.borrow().len() / 2;
-        let arr_l = Rc::new(RefCell::new(arr.borrow()[..mid].to_owned()));
-        let arr_r = Rc::new(RefCell::new(arr.borrow()[mid..].to_owned()));
+        let (arr_l, arr_r) = split(&mut self.base, &arr);
         let right = MergeState {
             arr,
             arr_l,
@@ -158,8 +233,24 @@ impl Merge {
             s: 0,
             sorted: false,
             start_i: state.start_i + state.arr_l.borrow().len(),
+            depth: state.depth + 1,
         };
 
         (left, right)
     }
 }
+
+/// Copy each half of arr into its own buffer
+fn split(base: &mut SortBase, arr: &Buffer) -> (Buffer, Buffer) {
+    let arr = arr.borrow();
+    let mid = arr.len() / 2;
+    for (i, value) in arr[..mid].iter().enumerate() {
+        base.aux_write(Aux::Left, i, *value);
+    }
+    for (i, value) in arr[mid..].iter().enumerate() {
+        base.aux_write(Aux::Right, i, *value);
+    }
+    let arr_l = Rc::new(RefCell::new(arr[..mid].to_owned()));
+    let arr_r = Rc::new(RefCell::new(arr[mid..].to_owned()));
+    (arr_l, arr_r)
+}
@@ -1,53 +1,101 @@
-use leptos::*;
-use rand::prelude::SliceRandom;
-use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{AudioContext, CanvasRenderingContext2d, OscillatorNode};
+use std::cmp::Ordering;
 
+pub mod benchmark;
+pub mod bins;
 mod bubble;
+pub mod complexity;
 mod counting;
 mod heap;
+mod history;
+pub mod image;
+pub mod input;
 mod insertion;
 mod merge;
+mod op;
+pub mod pitch;
 mod quick;
 mod radix;
+pub mod recursion;
+mod scale;
 mod selection;
+pub mod sound;
+pub mod soundtrack;
+pub mod stability;
+#[cfg(test)]
+mod tests;
 
-enum Color {
+pub use heap::HeapView;
+pub use history::History;
+pub use op::{Aux, AuxBuffer, Counters, Op};
+pub use recursion::Frame;
+pub use scale::Scale;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Color {
     Green,
     Light,
     Red,
+    /// One of [`stability::SHADES`].
+    Shade(u8),
 }
 
 impl Color {
-    fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             Self::Green => "#198754",
             Self::Light => "#dddddd",
             Self::Red => "#dc3545",
+            Self::Shade(shade) => stability::SHADES[*shade as usize],
         }
     }
-}
 
-pub struct SortParams<'a> {
-    pub canvas_ref: &'a NodeRef<html::Canvas>,
-    pub items: usize,
-    pub volume: RwSignal<f32>,
-    pub array_access: RwSignal<usize>,
-    pub array_swap: RwSignal<usize>,
-    pub array_cmp: RwSignal<usize>,
+    /// Red, green and blue of the color, for drawing pixels directly.
+    pub fn rgb(&self) -> [u8; 3] {
+        let hex = &self.as_str()[1..];
+        [0, 2, 4].map(|at| u8::from_str_radix(&hex[at..at + 2], 16).expect("hex color"))
+    }
 }
 
 pub trait VisualSort {
     fn new(base: SortBase) -> Self
     where
         Self: Sized;
-    fn done(&self) -> bool;
-    fn draw(&mut self, ticks: usize);
-    fn osc_stop(&self);
+    fn base(&self) -> &SortBase;
+    fn base_mut(&mut self) -> &mut SortBase;
+    fn color(&self, i: usize) -> Color;
+    /// Indices [`VisualSort::color`] can show as other than red at this step, so a view
+    /// can recolor those alone. Some may lie past the end of the data.
+    fn highlights(&self) -> Vec<usize>;
+    /// Line of [`Sort::pseudocode`] the latest step ended on.
+    fn line(&self) -> usize;
     fn update(&mut self);
+
+    /// Auxiliary buffers the sort holds at this step, none for sorts working in place.
+    fn aux(&self) -> Vec<AuxBuffer> {
+        Vec::new()
+    }
+
+    /// Number of values held in auxiliary buffers at this step.
+    fn aux_len(&self) -> usize {
+        0
+    }
+
+    /// Pending calls of a recursive sort, the current one last.
+    fn frames(&self) -> Vec<Frame> {
+        Vec::new()
+    }
+
+    /// The heap within the data, for sorts that build one.
+    fn heap(&self) -> Option<HeapView> {
+        None
+    }
+
+    fn done(&self) -> bool {
+        self.base().done
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Sort {
     Bubble,
     Counting,
@@ -60,6 +108,17 @@ pub enum Sort {
 }
 
 impl Sort {
+    pub const ALL: [Sort; 8] = [
+        Self::Bubble,
+        Self::Counting,
+        Self::Heap,
+        Self::Insertion,
+        Self::Merge,
+        Self::Radix,
+        Self::Quick,
+        Self::Selection,
+    ];
+
     pub fn name_as_str(&self) -> &'static str {
         match self {
             Self::Bubble => "Bubble Sort",
@@ -86,8 +145,46 @@ impl Sort {
         }
     }
 
-    pub fn init(&self, params: SortParams) -> Box<dyn VisualSort> {
-        let base = SortBase::new(params);
+    /// The sort at a route, with or without its leading slash.
+    pub fn from_route(route: &str) -> Option<Self> {
+        let route = route.strip_prefix('/').unwrap_or(route);
+        Self::ALL
+            .into_iter()
+            .find(|sort| sort.route_as_str()[1..] == *route)
+    }
+
+    /// Pseudocode with a line for each kind of step, loops folded into the first line
+    /// they run so every line is one a step can end on.
+    pub fn pseudocode(&self) -> &'static [&'static str] {
+        match self {
+            Self::Bubble => &bubble::PSEUDOCODE,
+            Self::Counting => &counting::PSEUDOCODE,
+            Self::Heap => &heap::PSEUDOCODE,
+            Self::Insertion => &insertion::PSEUDOCODE,
+            Self::Merge => &merge::PSEUDOCODE,
+            Self::Radix => &radix::PSEUDOCODE,
+            Self::Quick => &quick::PSEUDOCODE,
+            Self::Selection => &selection::PSEUDOCODE,
+        }
+    }
+
+    /// Whether the sort splits the data into calls on smaller parts.
+    pub fn recursive(&self) -> bool {
+        matches!(self, Self::Merge | Self::Quick)
+    }
+
+    /// Whether the sort works within the data alone, without auxiliary buffers.
+    pub fn in_place(&self) -> bool {
+        !matches!(self, Self::Counting | Self::Merge | Self::Radix)
+    }
+
+    /// Whether the sort moves elements, rather than rebuilding values from counts,
+    /// so that it can keep or lose the order of equal keys.
+    pub fn moves_elements(&self) -> bool {
+        !matches!(self, Self::Counting)
+    }
+
+    pub fn init(&self, base: SortBase) -> Box<dyn VisualSort> {
         match self {
             Self::Bubble => Box::new(bubble::Bubble::new(base)),
             Self::Counting => Box::new(counting::Counting::new(base)),
@@ -99,107 +196,140 @@ impl Sort {
             Self::Selection => Box::new(selection::Selection::new(base)),
         }
     }
+
+    /// Steps a run takes to sort the data from start to finish.
+    pub fn count_steps(&self, base: SortBase) -> usize {
+        StepCount::new(*self, base)
+            .work(usize::MAX)
+            .expect("sort to finish")
+    }
+}
+
+/// Counts the steps of a run a budget at a time, so a long run can be counted over many
+/// frames.
+pub struct StepCount {
+    sorter: Box<dyn VisualSort>,
+    steps: usize,
+}
+
+impl StepCount {
+    pub fn new(sort: Sort, base: SortBase) -> Self {
+        Self {
+            sorter: sort.init(base),
+            steps: 0,
+        }
+    }
+
+    /// Steps counted so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Step up to budget times, returning the count once sorted.
+    pub fn work(&mut self, budget: usize) -> Option<usize> {
+        for _ in 0..budget {
+            if self.sorter.done() {
+                break;
+            }
+            self.sorter.update();
+            self.sorter.base_mut().take_ops();
+            self.steps += 1;
+        }
+        self.sorter.done().then_some(self.steps)
+    }
 }
 
+/// Data buffer and operation log shared by all sorts, without any canvas or audio attached.
+///
+/// Sorts change the data through the methods here, so every step is recorded as [`Op`]s
+/// that accumulate until taken.
+#[derive(Clone)]
 pub struct SortBase {
-    array_access: RwSignal<usize>,
-    array_swap: RwSignal<usize>,
-    array_cmp: RwSignal<usize>,
-    canvas_h: f64,
-    canvas_w: f64,
-    ctx2d: CanvasRenderingContext2d,
     data: Vec<usize>,
     done: bool,
-    osc: OscillatorNode,
-    spacing: f64,
-    col_width: f64,
-    col_height_pct: f64,
+    ops: Vec<Op>,
+    scale: Scale,
 }
 
 impl SortBase {
-    pub fn new(params: SortParams) -> Self {
-        let mut rng = rand::thread_rng();
-        let mut data: Vec<usize> = (1..=params.items).collect();
-        data.shuffle(&mut rng);
-        let len = data.len() as f64;
-
-        let canvas = params
-            .canvas_ref
-            .get_untracked()
-            .expect("canvas should exist");
-        let canvas_w = canvas.client_width() as f64;
-        let canvas_h = canvas.client_height() as f64;
-        canvas.set_width(canvas_w as u32);
-        canvas.set_height(canvas_h as u32);
-
-        let ctx2d = canvas
-            .get_context("2d")
-            .unwrap()
-            .unwrap()
-            .dyn_into::<CanvasRenderingContext2d>()
-            .expect("canvas to have 2d context");
-
-        let audio_ctx = AudioContext::new().expect("to create audio context");
-        let audio_osc = audio_ctx.create_oscillator().expect("to create oscillator");
-        let audio_gain = audio_ctx.create_gain().expect("to create gain");
-        audio_gain.gain().set_value(0.0);
-        audio_osc
-            .connect_with_audio_node(&audio_gain)
-            .expect("audio connect gain");
-        audio_gain
-            .connect_with_audio_node(&audio_ctx.destination())
-            .expect("gain connect destination");
-        let _ = audio_osc.start();
-        create_effect(move |_| audio_gain.gain().set_value(params.volume.get()));
-
-        // no spacing if low pixel per item
-        let spacing = if canvas_w / len > 4.0 { 2.0 } else { 0.0 };
-
-        // how wide can one item be for all items to fill the canvas, no spacing front or end
-        let col_width = (canvas_w + spacing - (spacing * len)) / len;
-        let col_height_pct = canvas_h / len;
-
+    /// Empty data starts out sorted, so no sort steps through it.
+    pub fn new(data: Vec<usize>) -> Self {
         Self {
-            array_access: params.array_access,
-            array_swap: params.array_swap,
-            array_cmp: params.array_cmp,
-            canvas_h,
-            canvas_w,
-            ctx2d,
+            scale: Scale::new(&data),
+            done: data.is_empty(),
             data,
-            done: false,
-            osc: audio_osc,
-            spacing,
-            col_width,
-            col_height_pct,
+            ops: Vec::new(),
         }
     }
 
-    fn draw<F>(&mut self, set_color: F)
-    where
-        F: Fn(bool, usize) -> &'static str,
-    {
-        self.ctx2d
-            .clear_rect(0.0, 0.0, self.canvas_w, self.canvas_h);
-        // draw each item
-        for (i, num) in self.data.iter().enumerate() {
-            let y = *num as f64 * self.col_height_pct;
-            // draw item inside canvas, with width and spacing, no spacing front or end
-            let x = i as f64 * (self.col_width + self.spacing);
-            self.ctx2d
-                .set_fill_style(&JsValue::from(set_color(self.done, i)));
-            self.ctx2d.begin_path();
-            self.ctx2d.rect(x, self.canvas_h - y, self.col_width, y);
-            self.ctx2d.close_path();
-            self.ctx2d.fill();
+    /// Data of keys that each carry their original index, so equal keys can be told apart.
+    ///
+    /// Sorts order the data by key alone, see [`Scale::key`] and [`Scale::tag`].
+    pub fn tagged(keys: Vec<usize>) -> Self {
+        let stride = keys.len();
+        let data = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| key * stride + i)
+            .collect();
+        Self {
+            scale: Scale::tagged(&keys, stride),
+            done: keys.is_empty(),
+            data,
+            ops: Vec::new(),
         }
     }
 
-    fn set_freq(&self, value: usize) {
-        let start = 200.0;
-        let range = 400.0;
-        let len = self.data.len() as f32;
-        let freq = start + (range / len) * value as f32;
-        self.osc.frequency().set_value(freq);
+    pub fn data(&self) -> &[usize] {
+        &self.data
+    }
+
+    pub fn scale(&self) -> Scale {
+        self.scale
+    }
+
+    /// Operations recorded since the previous call.
+    pub fn take_ops(&mut self) -> Vec<Op> {
+        std::mem::take(&mut self.ops)
+    }
+
+    fn read(&mut self, i: usize) -> usize {
+        self.ops.push(Op::Read(i));
+        self.data[i]
+    }
+
+    /// Read the key of a value, see [`SortBase::tagged`].
+    fn read_key(&mut self, i: usize) -> usize {
+        let value = self.read(i);
+        self.scale.key(value)
+    }
+
+    fn cmp(&mut self, i: usize, j: usize) -> Ordering {
+        self.ops.push(Op::Compare(i, j));
+        self.scale
+            .key(self.data[i])
+            .cmp(&self.scale.key(self.data[j]))
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.ops.push(Op::Swap(i, j));
+        self.data.swap(i, j);
+    }
+
+    fn write(&mut self, i: usize, value: usize) {
+        self.ops.push(Op::Write(i, value));
+        self.data[i] = value;
+    }
+
+    fn aux_read(&mut self, aux: Aux, i: usize) {
+        self.ops.push(Op::AuxRead(aux, i));
+    }
+
+    fn aux_write(&mut self, aux: Aux, i: usize, value: usize) {
+        self.ops.push(Op::AuxWrite(aux, i, value));
+    }
+
+    fn aux_cmp(&mut self, a: Aux, i: usize, b: Aux, j: usize) {
+        self.ops.push(Op::AuxCompare(a, i, b, j));
     }
 }
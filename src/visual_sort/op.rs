@@ -0,0 +1,93 @@
+/// Auxiliary buffer kept by a sort next to its data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aux {
+    Count,
+    Left,
+    Right,
+    Tmp,
+}
+
+impl Aux {
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Count => "Count",
+            Self::Left => "Left",
+            Self::Right => "Right",
+            Self::Tmp => "Tmp",
+        }
+    }
+
+    /// Whether the buffer holds counts rather than values from the data.
+    pub fn holds_counts(&self) -> bool {
+        matches!(self, Self::Count)
+    }
+}
+
+/// Contents of an auxiliary buffer at the current step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuxBuffer {
+    pub aux: Aux,
+    pub values: Vec<usize>,
+    /// Index the sort is working at, with the values before it used up where that applies.
+    pub cursor: Option<usize>,
+}
+
+/// A single operation a sort performed, indices are into the data unless noted otherwise.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Op {
+    Read(usize),
+    Compare(usize, usize),
+    Swap(usize, usize),
+    /// Index and the value written to it.
+    Write(usize, usize),
+    AuxRead(Aux, usize),
+    /// Buffer, index into the buffer, and the value written to it.
+    AuxWrite(Aux, usize, usize),
+    AuxCompare(Aux, usize, Aux, usize),
+}
+
+impl Op {
+    /// Indices into the data touched by this operation.
+    pub fn indices(&self) -> impl Iterator<Item = usize> {
+        let (i, j) = match *self {
+            Self::Read(i) | Self::Write(i, _) => (Some(i), None),
+            Self::Compare(i, j) | Self::Swap(i, j) => (Some(i), Some(j)),
+            Self::AuxRead(..) | Self::AuxWrite(..) | Self::AuxCompare(..) => (None, None),
+        };
+        i.into_iter().chain(j)
+    }
+
+    /// Indices into the data whose value this operation changed.
+    pub fn changes(&self) -> impl Iterator<Item = usize> {
+        let changes = matches!(self, Self::Swap(..) | Self::Write(..));
+        self.indices().filter(move |_| changes)
+    }
+
+    /// Last index into the data touched by this operation.
+    pub fn index(&self) -> Option<usize> {
+        self.indices().last()
+    }
+}
+
+/// Totals derived from the operations of a sort.
+///
+/// Reads count one access and comparisons two, while swaps and writes count one update each.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    pub accesses: usize,
+    pub updates: usize,
+    pub comparisons: usize,
+}
+
+impl Counters {
+    pub fn add(&mut self, op: &Op) {
+        match op {
+            Op::Read(..) | Op::AuxRead(..) => self.accesses += 1,
+            Op::Compare(..) | Op::AuxCompare(..) => {
+                self.accesses += 2;
+                self.comparisons += 1;
+            }
+            Op::Swap(..) | Op::Write(..) | Op::AuxWrite(..) => self.updates += 1,
+        }
+    }
+}
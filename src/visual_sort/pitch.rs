@@ -0,0 +1,210 @@
+/// How values are spread between the lowest and highest frequency.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mapping {
+    /// Equal steps in Hz, crowding the high notes together to the ear.
+    Linear,
+    /// Equal steps in pitch, as the ear hears them.
+    Logarithmic,
+}
+
+impl Mapping {
+    pub const ALL: [Mapping; 2] = [Self::Linear, Self::Logarithmic];
+
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Logarithmic => "Logarithmic",
+        }
+    }
+
+    pub fn key_as_str(&self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::Logarithmic => "log",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.key_as_str() == key)
+    }
+}
+
+/// Musical scale frequencies are rounded to, if any.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tuning {
+    Continuous,
+    Chromatic,
+    Major,
+    Pentatonic,
+}
+
+impl Tuning {
+    pub const ALL: [Tuning; 4] = [
+        Self::Continuous,
+        Self::Chromatic,
+        Self::Major,
+        Self::Pentatonic,
+    ];
+
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Continuous => "Continuous",
+            Self::Chromatic => "Chromatic",
+            Self::Major => "Major",
+            Self::Pentatonic => "Pentatonic",
+        }
+    }
+
+    pub fn key_as_str(&self) -> &'static str {
+        match self {
+            Self::Continuous => "continuous",
+            Self::Chromatic => "chromatic",
+            Self::Major => "major",
+            Self::Pentatonic => "pentatonic",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.key_as_str() == key)
+    }
+
+    /// Semitones above the root of the notes in an octave, None for any frequency.
+    pub fn degrees(&self) -> Option<&'static [i32]> {
+        match self {
+            Self::Continuous => None,
+            Self::Chromatic => Some(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+            Self::Major => Some(&[0, 2, 4, 5, 7, 9, 11]),
+            Self::Pentatonic => Some(&[0, 2, 4, 7, 9]),
+        }
+    }
+}
+
+/// Frequency a value is heard at, by where it lies between the smallest and largest value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Pitch {
+    pub min_hz: f64,
+    pub max_hz: f64,
+    pub mapping: Mapping,
+    /// Scale rooted at the note nearest `min_hz`.
+    pub tuning: Tuning,
+}
+
+impl Default for Pitch {
+    fn default() -> Self {
+        Self {
+            min_hz: 200.0,
+            max_hz: 600.0,
+            mapping: Mapping::Linear,
+            tuning: Tuning::Continuous,
+        }
+    }
+}
+
+impl Pitch {
+    /// Lowest frequency allowed, keeping the logarithmic mapping defined
+    pub const LOWEST_HZ: f64 = 20.0;
+    /// Highest frequency allowed
+    pub const HIGHEST_HZ: f64 = 20_000.0;
+
+    /// Frequency in Hz for a value placed at fraction between the smallest and largest value.
+    pub fn hz(&self, fraction: f64) -> f64 {
+        let min = self.min_hz.clamp(Self::LOWEST_HZ, Self::HIGHEST_HZ);
+        let max = self.max_hz.clamp(min, Self::HIGHEST_HZ);
+        let fraction = fraction.clamp(0.0, 1.0);
+        let hz = match self.mapping {
+            Mapping::Linear => min + (max - min) * fraction,
+            Mapping::Logarithmic => min * (max / min).powf(fraction),
+        };
+        let Some(degrees) = self.tuning.degrees() else {
+            return hz;
+        };
+
+        // in semitones from the root, rounded to the nearest note of the scale
+        let root = semitones(min).round();
+        let above = semitones(hz) - root;
+        let octave = (above / 12.0).floor();
+        let within = above - octave * 12.0;
+        let nearest = degrees
+            .iter()
+            .map(|&d| d as f64)
+            .chain([12.0])
+            .min_by(|a, b| (a - within).abs().total_cmp(&(b - within).abs()))
+            .expect("scale to have notes");
+        frequency(root + octave * 12.0 + nearest)
+    }
+}
+
+/// Semitones above A4, or MIDI note number less 69
+fn semitones(hz: f64) -> f64 {
+    12.0 * (hz / 440.0).log2()
+}
+
+fn frequency(semitones: f64) -> f64 {
+    440.0 * 2f64.powf(semitones / 12.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn default_is_linear_from_200_to_600() {
+        let pitch = Pitch::default();
+        for fraction in [0.0, 0.25, 0.5, 1.0] {
+            assert!(close(pitch.hz(fraction), 200.0 + 400.0 * fraction));
+        }
+    }
+
+    #[test]
+    fn logarithmic_steps_are_equal_ratios() {
+        let pitch = Pitch {
+            min_hz: 110.0,
+            max_hz: 880.0,
+            mapping: Mapping::Logarithmic,
+            ..Pitch::default()
+        };
+        assert!(close(pitch.hz(0.0), 110.0));
+        assert!(close(pitch.hz(1.0 / 3.0), 220.0));
+        assert!(close(pitch.hz(2.0 / 3.0), 440.0));
+        assert!(close(pitch.hz(1.0), 880.0));
+    }
+
+    #[test]
+    fn tuned_pitches_fall_on_the_scale() {
+        for tuning in [Tuning::Chromatic, Tuning::Major, Tuning::Pentatonic] {
+            let pitch = Pitch {
+                min_hz: 220.0,
+                max_hz: 880.0,
+                tuning,
+                ..Pitch::default()
+            };
+            let degrees = tuning.degrees().unwrap();
+            for n in 0..=100 {
+                let hz = pitch.hz(n as f64 / 100.0);
+                let note = semitones(hz) - semitones(220.0);
+                assert!(close(note, note.round()), "{tuning:?} {hz}");
+                let degree = (note.round() as i32).rem_euclid(12);
+                assert!(degrees.contains(&degree), "{tuning:?} {hz}");
+            }
+            // the root itself and the octaves above are always in the scale
+            assert!(close(pitch.hz(0.0), 220.0));
+            assert!(close(pitch.hz(1.0), 880.0));
+        }
+    }
+
+    #[test]
+    fn range_is_kept_audible_and_ordered() {
+        let pitch = Pitch {
+            min_hz: 0.0,
+            max_hz: -5.0,
+            mapping: Mapping::Logarithmic,
+            ..Pitch::default()
+        };
+        assert!(pitch.hz(0.5).is_finite());
+        assert!(close(pitch.hz(1.0), Pitch::LOWEST_HZ));
+    }
+}
@@ -1,41 +1,57 @@
-use super::{Color, SortBase, VisualSort};
-use leptos::*;
+use super::{Color, Frame, SortBase, VisualSort};
 
 struct QuickState {
     lo: usize,
     hi: usize,
     pivot: usize,
     i: usize,
+    depth: usize,
 }
 
+pub(super) const PSEUDOCODE: [&str; 3] = [
+    "quick_sort(lo, hi), pivot = a[hi], p = lo, for i in lo..hi: if a[i] <= pivot",
+    "    swap a[i], a[p], p = p + 1",
+    "swap a[hi], a[p], then quick_sort(lo, p - 1), quick_sort(p + 1, hi)",
+];
+const COMPARE: usize = 0;
+const SWAP_LOWER: usize = 1;
+const PLACE_PIVOT: usize = 2;
+
 pub struct Quick {
     base: SortBase,
+    line: usize,
     pivots: Vec<QuickState>,
 }
 
 impl VisualSort for Quick {
     fn new(base: SortBase) -> Self {
-        let hi = base.data.len() - 1;
-        Self {
-            base,
-            pivots: vec![QuickState {
+        // empty data has nothing to partition
+        let pivots = match base.data.len() {
+            0 => Vec::new(),
+            len => vec![QuickState {
                 lo: 0,
-                hi,
+                hi: len - 1,
                 pivot: 0,
                 i: 0,
+                depth: 0,
             }],
+        };
+        Self {
+            base,
+            line: 0,
+            pivots,
         }
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
-        }
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
+    }
 
+    fn color(&self, i: usize) -> Color {
         let (curr_pivot, curr_lo, curr_hi, curr_i) = match self.pivots.last() {
             Some(state) => (
                 state.pivot.saturating_sub(1),
@@ -43,22 +59,43 @@ impl VisualSort for Quick {
                 state.hi,
                 state.i.saturating_sub(1),
             ),
-            None => (0, 0, self.base.data.len() - 1, 0),
+            None => (0, 0, self.base.data.len().saturating_sub(1), 0),
         };
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && (i == curr_hi || i == curr_lo) {
-                Color::Green.as_str()
-            } else if !done && (i == curr_pivot || i == curr_i) {
-                Color::Light.as_str()
-            } else {
-                Color::Red.as_str()
-            }
-        });
+        if i == curr_hi || i == curr_lo {
+            Color::Green
+        } else if i == curr_pivot || i == curr_i {
+            Color::Light
+        } else {
+            Color::Red
+        }
+    }
+
+    fn highlights(&self) -> Vec<usize> {
+        match self.pivots.last() {
+            Some(state) => vec![
+                state.pivot.saturating_sub(1),
+                state.lo,
+                state.hi,
+                state.i.saturating_sub(1),
+            ],
+            None => vec![0, self.base.data.len().saturating_sub(1)],
+        }
+    }
+
+    fn frames(&self) -> Vec<Frame> {
+        self.pivots
+            .iter()
+            .map(|state| Frame {
+                start: state.lo,
+                end: state.hi + 1,
+                depth: state.depth,
+            })
+            .collect()
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
@@ -71,20 +108,18 @@ impl VisualSort for Quick {
             }
         };
 
-        // find all less or equal to pivot, return on tick
-        for i in state.i..state.hi {
-            self.base.array_access.update(|n| *n += 2);
-            self.base.array_cmp.update(|n| *n += 1);
-            if self.base.data[i] <= self.base.data[state.hi] {
-                self.base.array_swap.update(|n| *n += 1);
-                self.base.data.swap(i, state.pivot);
-                self.base.set_freq(self.base.data[state.pivot]);
-                // tick done
+        // compare one with the pivot each step, moving it below when less or equal
+        if state.i < state.hi {
+            let i = state.i;
+            self.line = COMPARE;
+            if self.base.cmp(i, state.hi).is_le() {
+                self.base.swap(i, state.pivot);
+                self.line = SWAP_LOWER;
                 state.pivot += 1;
-                state.i = i + 1;
-                self.pivots.push(state);
-                return;
             }
+            state.i = i + 1;
+            self.pivots.push(state);
+            return;
         }
 
         // when all less or equal to pivot has been found
@@ -92,8 +127,8 @@ impl VisualSort for Quick {
         if state.pivot >= self.base.data.len() {
             state.pivot = self.base.data.len() - 1;
         }
-        self.base.data.swap(state.hi, state.pivot);
-        self.base.array_swap.update(|n| *n += 1);
+        self.base.swap(state.hi, state.pivot);
+        self.line = PLACE_PIVOT;
 
         // add state for upper half of pivot
         if state.pivot + 1 < state.hi {
@@ -102,6 +137,7 @@ impl VisualSort for Quick {
                 hi: state.hi,
                 pivot: state.pivot + 1,
                 i: state.pivot + 1,
+                depth: state.depth + 1,
             });
         }
 
@@ -112,7 +148,45 @@ impl VisualSort for Quick {
                 hi: state.pivot - 1,
                 pivot: state.lo,
                 i: state.lo,
+                depth: state.depth + 1,
             });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::inputs;
+
+    #[test]
+    fn partition_invariant_holds_each_step() {
+        for input in inputs() {
+            let mut quick = Quick::new(SortBase::new(input));
+            while !quick.done() {
+                quick.update();
+                let data = &quick.base.data;
+                for state in &quick.pivots {
+                    assert!(state.lo <= state.pivot && state.pivot <= state.i);
+                    assert!(state.i <= state.hi && state.hi < data.len());
+                    let pivot = data[state.hi];
+                    assert!(data[state.lo..state.pivot].iter().all(|&v| v <= pivot));
+                    assert!(data[state.pivot..state.i].iter().all(|&v| v > pivot));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pending_ranges_are_disjoint() {
+        for input in inputs() {
+            let mut quick = Quick::new(SortBase::new(input));
+            while !quick.done() {
+                quick.update();
+                let mut ranges: Vec<_> = quick.pivots.iter().map(|s| (s.lo, s.hi)).collect();
+                ranges.sort_unstable();
+                assert!(ranges.windows(2).all(|w| w[0].1 < w[1].0));
+            }
+        }
+    }
+}
@@ -1,11 +1,25 @@
-use super::{Color, SortBase, VisualSort};
-use leptos::*;
+use super::{Aux, AuxBuffer, Color, SortBase, VisualSort};
+
+pub(super) const PSEUDOCODE: [&str; 5] = [
+    "max = largest of a",
+    "for each digit d of max, lowest first: tmp = copy of a, count = zeros(10)",
+    "    for v in a: count[digit d of v] += 1",
+    "    add the counts of higher digits to each count",
+    "    for v in tmp: a[n - count[digit d of v]] = v, count[digit d of v] -= 1",
+];
+const MAX: usize = 0;
+const NEXT_DIGIT: usize = 1;
+const COUNT: usize = 2;
+const SUM_COUNTS: usize = 3;
+const OUTPUT: usize = 4;
 
 pub struct Radix {
     base: SortBase,
+    line: usize,
     count: [usize; 10],
     counted: bool,
     max: usize,
+    max_i: usize,
     maxed: bool,
     y: usize,
     x: usize,
@@ -15,109 +29,151 @@ pub struct Radix {
 
 impl VisualSort for Radix {
     fn new(base: SortBase) -> Self {
-        let tmp_data = base.data.clone();
-        Self {
+        let mut radix = Self {
             base,
+            line: 0,
             count: [0; 10],
             counted: false,
             max: 0,
+            max_i: 0,
             maxed: false,
             y: 0,
             x: 0,
             radix: 0,
-            tmp_data,
-        }
+            tmp_data: Vec::new(),
+        };
+        radix.copy_tmp();
+        radix
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
-        }
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
+    }
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && i == self.x.saturating_sub(1) {
-                if !self.maxed || !self.counted {
-                    Color::Light.as_str()
-                } else {
-                    Color::Green.as_str()
-                }
+    fn color(&self, i: usize) -> Color {
+        if i == self.x.saturating_sub(1) {
+            if !self.maxed || !self.counted {
+                Color::Light
             } else {
-                Color::Red.as_str()
+                Color::Green
             }
-        });
+        } else {
+            Color::Red
+        }
+    }
+
+    fn highlights(&self) -> Vec<usize> {
+        vec![self.x.saturating_sub(1)]
+    }
+
+    fn aux(&self) -> Vec<AuxBuffer> {
+        vec![
+            AuxBuffer {
+                aux: Aux::Count,
+                values: self.count.to_vec(),
+                cursor: None,
+            },
+            AuxBuffer {
+                aux: Aux::Tmp,
+                values: self.tmp_data.clone(),
+                cursor: self.counted.then_some(self.y),
+            },
+        ]
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn aux_len(&self) -> usize {
+        self.count.len() + self.tmp_data.len()
+    }
+
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
+        // nothing to read in empty data, which is done from the start
+        if self.base.data.is_empty() {
+            return;
+        }
+
         // find max value
         if !self.maxed {
-            self.base.array_access.update(|n| *n += 1);
-            self.base.array_cmp.update(|n| *n += 1);
-            if self.base.data[self.x] > self.max {
-                self.max = self.base.data[self.x];
-                self.base.set_freq(self.max);
+            self.line = MAX;
+            if self.x == 0 || self.base.cmp(self.x, self.max_i).is_gt() {
+                self.max_i = self.x;
             }
             self.x += 1;
             if self.x < self.base.data.len() {
                 return;
             }
             self.x = 0;
+            self.max = self.base.read_key(self.max_i);
             self.maxed = true;
         }
 
         // count values from 0 to max
         if !self.counted {
-            self.base.array_access.update(|n| *n += 1);
-            self.base.array_swap.update(|n| *n += 1);
-            let value = self.base.data[self.x];
-            let base = value / 10_usize.pow(self.radix) % 10;
+            self.line = COUNT;
+            let key = self.base.read_key(self.x);
+            let base = key / 10_usize.pow(self.radix) % 10;
             self.count[base] += 1;
-            self.base.set_freq(self.base.data[self.x]);
+            self.base.aux_write(Aux::Count, base, self.count[base]);
             self.x += 1;
             if self.x < self.base.data.len() {
                 return;
             }
             self.x = 0;
             self.counted = true;
+            self.line = SUM_COUNTS;
             for i in (0..self.count.len() - 1).rev() {
-                self.base.array_swap.update(|n| *n += 1);
+                self.base.aux_read(Aux::Count, i + 1);
                 self.count[i] += self.count[i + 1];
+                self.base.aux_write(Aux::Count, i, self.count[i]);
             }
+            // the sums get a step of their own
+            return;
         }
 
         // update data based on count results
         if self.y < self.tmp_data.len() {
-            self.base.array_access.update(|n| *n += 2);
+            self.base.aux_read(Aux::Tmp, self.y);
             let value = self.tmp_data[self.y];
-            let base = value / 10_usize.pow(self.radix) % 10;
+            let base = self.base.scale.key(value) / 10_usize.pow(self.radix) % 10;
             let i = self.tmp_data.len() - self.count[base];
-            self.base.array_swap.update(|n| *n += 2);
             self.count[base] -= 1;
-            self.base.data[i] = value;
-            self.base.set_freq(value);
+            self.base.aux_write(Aux::Count, base, self.count[base]);
+            self.base.write(i, value);
+            self.line = OUTPUT;
             self.x = i;
             self.y += 1;
             return;
         }
 
         // done if max < 10^radix
-        self.base.array_cmp.update(|n| *n += 1);
         if self.max >= 10_usize.pow(self.radix + 1) {
             self.x = 0;
             self.y = 0;
             self.count = [0; 10];
             self.counted = false;
             self.radix += 1;
-            self.tmp_data = self.base.data.clone();
+            self.copy_tmp();
+            self.line = NEXT_DIGIT;
         } else {
             self.base.done = true
         }
     }
 }
+
+impl Radix {
+    fn copy_tmp(&mut self) {
+        self.tmp_data.clear();
+        for i in 0..self.base.data.len() {
+            let value = self.base.read(i);
+            self.base.aux_write(Aux::Tmp, i, value);
+            self.tmp_data.push(value);
+        }
+    }
+}
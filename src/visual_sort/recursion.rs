@@ -0,0 +1,129 @@
+/// A pending call of a recursive sort, working on `data[start..end]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub start: usize,
+    pub end: usize,
+    /// Calls above this one, zero for the first.
+    pub depth: usize,
+}
+
+/// A call seen while sorting, finished once it left the stack.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub frame: Frame,
+    pub finished: bool,
+}
+
+/// Every call a recursive sort has made so far, in the order they were first seen.
+#[derive(Clone, Debug, Default)]
+pub struct CallTree {
+    calls: Vec<Call>,
+    /// Indices of calls still on the stack
+    active: Vec<usize>,
+    max_depth: usize,
+}
+
+impl CallTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Take in the stack after a step, adding new calls and finishing the ones gone from it.
+    pub fn update(&mut self, frames: &[Frame]) {
+        let calls = &mut self.calls;
+        self.active.retain(|&i| {
+            let on_stack = frames.contains(&calls[i].frame);
+            calls[i].finished = !on_stack;
+            on_stack
+        });
+        for frame in frames {
+            if !self.active.iter().any(|&i| calls[i].frame == *frame) {
+                self.active.push(calls.len());
+                calls.push(Call {
+                    frame: *frame,
+                    finished: false,
+                });
+                self.max_depth = self.max_depth.max(frame.depth);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::inputs;
+    use crate::visual_sort::{Sort, SortBase};
+
+    /// Step a sort to the end, following its stack
+    fn call_tree(sort: Sort, input: Vec<usize>) -> CallTree {
+        let mut sorter = sort.init(SortBase::new(input));
+        let mut tree = CallTree::new();
+        tree.update(&sorter.frames());
+        while !sorter.done() {
+            sorter.update();
+            let frames = sorter.frames();
+            for frame in &frames {
+                assert!(frame.start < frame.end && frame.end <= sorter.base().data().len());
+            }
+            tree.update(&frames);
+        }
+        tree
+    }
+
+    #[test]
+    fn every_call_finishes() {
+        for sort in [Sort::Merge, Sort::Quick] {
+            for input in inputs() {
+                let empty = input.is_empty();
+                let tree = call_tree(sort, input);
+                assert_eq!(tree.calls().is_empty(), empty);
+                assert!(tree.calls().iter().all(|call| call.finished));
+            }
+        }
+    }
+
+    #[test]
+    fn calls_nest_in_their_parent() {
+        for sort in [Sort::Merge, Sort::Quick] {
+            for input in inputs() {
+                let tree = call_tree(sort, input);
+                let calls = tree.calls();
+                for call in calls.iter().filter(|call| call.frame.depth > 0) {
+                    let Frame { start, end, depth } = call.frame;
+                    assert!(
+                        calls.iter().any(|parent| parent.frame.depth == depth - 1
+                            && parent.frame.start <= start
+                            && end <= parent.frame.end),
+                        "{sort:?} call {:?} has no parent",
+                        call.frame
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn merge_depth_is_logarithmic() {
+        for n in [2, 3, 16, 17, 100] {
+            let tree = call_tree(Sort::Merge, (0..n).rev().collect());
+            // halves of one item return at once and never show on the stack
+            let depth = (n as f64).log2().ceil() as usize - 1;
+            assert_eq!(tree.max_depth(), depth, "{n} items");
+        }
+    }
+
+    #[test]
+    fn iterative_sorts_have_no_stack() {
+        let sorter = Sort::Bubble.init(SortBase::new(vec![3, 1, 2]));
+        assert!(sorter.frames().is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+/// Smallest and largest value in the data, which sorting never changes.
+///
+/// Tagged data packs a key and the original index of an element into each value, and the
+/// range then covers the keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Scale {
+    pub min: usize,
+    pub max: usize,
+    stride: usize,
+}
+
+impl Scale {
+    pub fn new(data: &[usize]) -> Self {
+        Self {
+            min: data.iter().copied().min().unwrap_or(0),
+            max: data.iter().copied().max().unwrap_or(0),
+            stride: 1,
+        }
+    }
+
+    /// Scale of keys tagged with original indices below stride.
+    pub fn tagged(keys: &[usize], stride: usize) -> Self {
+        Self {
+            stride: stride.max(1),
+            ..Self::new(keys)
+        }
+    }
+
+    pub fn is_tagged(&self) -> bool {
+        self.stride > 1
+    }
+
+    /// The key a value is sorted by.
+    pub fn key(&self, value: usize) -> usize {
+        value / self.stride
+    }
+
+    /// The original index a value is tagged with, zero for untagged data.
+    pub fn tag(&self, value: usize) -> usize {
+        value % self.stride
+    }
+
+    /// Value of a key without a tag.
+    pub fn untagged(&self, key: usize) -> usize {
+        key * self.stride
+    }
+
+    /// Where a value lies from the smallest to the largest, above zero so the smallest still shows.
+    pub fn fraction(&self, value: usize) -> f64 {
+        let steps = (self.max - self.min + 1) as f64;
+        (self.key(value).saturating_sub(self.min) + 1) as f64 / steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fraction_of_range() {
+        let scale = Scale::new(&[4, 1, 3, 2]);
+        assert_eq!(scale.fraction(1), 0.25);
+        assert_eq!(scale.fraction(4), 1.0);
+
+        let scale = Scale::new(&[1_000_000, 500_001, 500_001]);
+        assert_eq!(scale.fraction(500_001), 1.0 / 500_000.0);
+        assert_eq!(scale.fraction(1_000_000), 1.0);
+
+        assert_eq!(Scale::new(&[7, 7]).fraction(7), 1.0);
+    }
+
+    #[test]
+    fn tagged_values_scale_by_key() {
+        let scale = Scale::tagged(&[2, 1, 2], 3);
+        let value = 2 * 3 + 2;
+        assert_eq!((scale.key(value), scale.tag(value)), (2, 2));
+        assert_eq!(scale.fraction(value), 1.0);
+        assert_eq!(scale.fraction(scale.untagged(1)), 0.5);
+    }
+}
@@ -1,8 +1,17 @@
 use super::{Color, SortBase, VisualSort};
-use leptos::*;
+
+pub(super) const PSEUDOCODE: [&str; 3] = [
+    "for x in 0..n - 1, min = x, y in x + 1..n: if a[y] < a[min]",
+    "    min = y",
+    "swap a[x], a[min] once y reaches n",
+];
+const COMPARE: usize = 0;
+const NEW_MIN: usize = 1;
+const SWAP: usize = 2;
 
 pub struct Selection {
     base: SortBase,
+    line: usize,
     s: usize,
     x: usize,
     y: usize,
@@ -12,56 +21,57 @@ impl VisualSort for Selection {
     fn new(base: SortBase) -> Self {
         Self {
             base,
+            line: 0,
             s: 0,
             x: 0,
             y: 0,
         }
     }
 
-    fn done(&self) -> bool {
-        self.base.done
+    fn base(&self) -> &SortBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut SortBase {
+        &mut self.base
     }
 
-    fn draw(&mut self, ticks: usize) {
-        for _ in 0..ticks {
-            self.update();
+    fn color(&self, i: usize) -> Color {
+        if i == self.y {
+            Color::Light
+        } else if i == self.x || i == self.s {
+            Color::Green
+        } else {
+            Color::Red
         }
+    }
 
-        self.base.draw(|done: bool, i: usize| {
-            if !done && i == self.y {
-                Color::Light.as_str()
-            } else if !done && (i == self.x || i == self.s) {
-                Color::Green.as_str()
-            } else {
-                Color::Red.as_str()
-            }
-        });
+    fn highlights(&self) -> Vec<usize> {
+        vec![self.y, self.x, self.s]
     }
 
-    fn osc_stop(&self) {
-        let _ = self.base.osc.stop();
+    fn line(&self) -> usize {
+        self.line
     }
 
     fn update(&mut self) {
-        if self.x < self.base.data.len() - 1 {
+        if self.x + 1 < self.base.data.len() {
             if self.y < self.base.data.len() {
-                self.base.array_access.update(|n| *n += 2);
-                self.base.array_cmp.update(|n| *n += 1);
-                if self.base.data[self.y] < self.base.data[self.s] {
+                self.line = COMPARE;
+                if self.base.cmp(self.y, self.s).is_lt() {
                     self.s = self.y;
-                    self.base.set_freq(self.base.data[self.s]);
+                    self.line = NEW_MIN;
                 }
                 self.y += 1;
                 return;
             }
 
-            self.base.array_swap.update(|n| *n += 1);
-            self.base.data.swap(self.x, self.s);
+            self.base.swap(self.x, self.s);
+            self.line = SWAP;
 
             self.x += 1;
             self.s = self.x;
             self.y = self.x + 1;
-            self.base.set_freq(self.base.data[self.s]);
         } else {
             self.base.done = true;
         }
@@ -0,0 +1,283 @@
+use super::{Op, SortBase};
+use std::f64::consts::TAU;
+
+/// Shape of the wave a tone is played with.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Sawtooth,
+    Triangle,
+}
+
+impl Waveform {
+    pub const ALL: [Waveform; 4] = [Self::Sine, Self::Square, Self::Sawtooth, Self::Triangle];
+
+    pub fn name_as_str(&self) -> &'static str {
+        match self {
+            Self::Sine => "Sine",
+            Self::Square => "Square",
+            Self::Sawtooth => "Sawtooth",
+            Self::Triangle => "Triangle",
+        }
+    }
+
+    pub fn key_as_str(&self) -> &'static str {
+        match self {
+            Self::Sine => "sine",
+            Self::Square => "square",
+            Self::Sawtooth => "sawtooth",
+            Self::Triangle => "triangle",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|w| w.key_as_str() == key)
+    }
+
+    /// The wave at phase, in cycles, between -1 and 1.
+    pub fn sample(&self, phase: f64) -> f64 {
+        let phase = phase.rem_euclid(1.0);
+        match self {
+            Self::Sine => (phase * TAU).sin(),
+            Self::Square if phase < 0.5 => 1.0,
+            Self::Square => -1.0,
+            Self::Sawtooth => 2.0 * phase - 1.0,
+            Self::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+}
+
+/// Attack, decay, sustain and release of a note, times in seconds.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Envelope {
+    pub attack: f64,
+    pub decay: f64,
+    /// Level held after the decay, from 0 to 1.
+    pub sustain: f64,
+    pub release: f64,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack: 0.005,
+            decay: 0.02,
+            sustain: 0.6,
+            release: 0.03,
+        }
+    }
+}
+
+impl Envelope {
+    /// Times and levels the note moves between in straight lines, over a note of length
+    /// seconds from start to silence.
+    ///
+    /// Notes too short for the whole envelope get it squeezed to fit, so one note is silent
+    /// before the next one starts.
+    pub fn breakpoints(&self, length: f64) -> [(f64, f64); 5] {
+        let length = length.max(0.0);
+        let shaped = self.attack + self.decay + self.release;
+        let squeeze = if shaped > length && shaped > 0.0 {
+            length / shaped
+        } else {
+            1.0
+        };
+        let attack = self.attack * squeeze;
+        let decay = attack + self.decay * squeeze;
+        let release = length - self.release * squeeze;
+        [
+            (0.0, 0.0),
+            (attack, 1.0),
+            (decay, self.sustain),
+            (release.max(decay), self.sustain),
+            (length, 0.0),
+        ]
+    }
+
+    /// Level of a note of length seconds at time t after it started.
+    pub fn level(&self, length: f64, t: f64) -> f64 {
+        let points = self.breakpoints(length);
+        points.windows(2).find(|w| t < w[1].0).map_or(0.0, |w| {
+            let ((t0, l0), (t1, l1)) = (w[0], w[1]);
+            if t < t0 {
+                0.0
+            } else {
+                l0 + (l1 - l0) * (t - t0) / (t1 - t0)
+            }
+        })
+    }
+}
+
+/// How an operation is voiced, so comparisons, writes and swaps sound apart.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Voice {
+    /// A soft click, for reads and comparisons.
+    Click,
+    /// A tone of the value written.
+    Tone,
+    /// Both values of a swap at once.
+    Chord,
+}
+
+impl Voice {
+    pub fn envelope(&self) -> Envelope {
+        match self {
+            Self::Click => Envelope {
+                attack: 0.001,
+                decay: 0.015,
+                sustain: 0.0,
+                release: 0.0,
+            },
+            Self::Tone => Envelope::default(),
+            Self::Chord => Envelope {
+                sustain: 0.8,
+                ..Envelope::default()
+            },
+        }
+    }
+
+    /// Loudness of each pitch in the voice, from 0 to 1.
+    pub fn level(&self) -> f64 {
+        match self {
+            Self::Click => 0.4,
+            Self::Tone => 1.0,
+            Self::Chord => 0.6,
+        }
+    }
+}
+
+/// A note sounding an operation on the data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    pub voice: Voice,
+    /// Values the note is pitched by, as fractions between the smallest and largest value.
+    pub fractions: Vec<f64>,
+    /// Stereo position by where in the data the operation was, -1 left to 1 right.
+    pub pan: f64,
+}
+
+impl Note {
+    /// The note for an operation, taking values from the data after the step it was part of.
+    ///
+    /// None for operations on auxiliary buffers alone.
+    pub fn of(op: &Op, base: &SortBase) -> Option<Self> {
+        let (data, scale) = (base.data(), base.scale());
+        let fraction = |i: usize| scale.fraction(data[i]);
+        let pan = |i: usize| match data.len() {
+            0 | 1 => 0.0,
+            len => 2.0 * i as f64 / (len - 1) as f64 - 1.0,
+        };
+        let (voice, fractions, pan) = match *op {
+            Op::Read(i) => (Voice::Click, vec![fraction(i)], pan(i)),
+            Op::Compare(_, j) => (Voice::Click, vec![fraction(j)], pan(j)),
+            Op::Write(i, _) => (Voice::Tone, vec![fraction(i)], pan(i)),
+            Op::Swap(i, j) => (
+                Voice::Chord,
+                vec![fraction(i), fraction(j)],
+                (pan(i) + pan(j)) / 2.0,
+            ),
+            Op::AuxRead(..) | Op::AuxWrite(..) | Op::AuxCompare(..) => return None,
+        };
+        Some(Self {
+            voice,
+            fractions,
+            pan,
+        })
+    }
+}
+
+/// Most notes sounded for one frame, more are thinned out evenly
+pub const MAX_NOTES: usize = 64;
+/// Longest a frame of notes is spread over in seconds, so a pause does not stretch the next
+pub const MAX_SPAN: f64 = 0.1;
+
+/// Spread the notes of a frame one after the other over its span in seconds, each sounding
+/// until the next starts.
+///
+/// Gives the start of each note from the start of the frame, the note and its length.
+pub fn schedule(notes: &[Note], span: f64) -> impl Iterator<Item = (f64, &Note, f64)> {
+    let count = notes.len().min(MAX_NOTES);
+    let gap = span / count.max(1) as f64;
+    (0..count).map(move |n| (n as f64 * gap, &notes[n * notes.len() / count], gap))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::Aux;
+
+    #[test]
+    fn envelope_fits_the_note() {
+        let envelope = Envelope::default();
+        for length in [0.0, 0.001, 0.02, 0.1, 1.0] {
+            let points = envelope.breakpoints(length);
+            assert!(points.windows(2).all(|w| w[0].0 <= w[1].0), "{length}");
+            assert_eq!(points[4], (length, 0.0));
+        }
+        let long = envelope.breakpoints(1.0);
+        assert_eq!(long[1], (0.005, 1.0));
+        assert!((long[3].0 - 0.97).abs() < 1e-12);
+
+        assert_eq!(envelope.level(1.0, 0.0), 0.0);
+        assert_eq!(envelope.level(1.0, 0.005), 1.0);
+        assert!((envelope.level(1.0, 0.5) - 0.6).abs() < 1e-12);
+        assert_eq!(envelope.level(1.0, 1.0), 0.0);
+        assert_eq!(envelope.level(1.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn waveforms_span_the_full_range() {
+        for waveform in Waveform::ALL {
+            let samples: Vec<f64> = (0..100)
+                .map(|n| waveform.sample(n as f64 / 100.0))
+                .collect();
+            let max = samples.iter().copied().fold(f64::MIN, f64::max);
+            let min = samples.iter().copied().fold(f64::MAX, f64::min);
+            // a sawtooth only nears its top before it wraps
+            assert!(max > 0.95 && max <= 1.0, "{waveform:?}");
+            assert!((min + 1.0).abs() < 1e-9, "{waveform:?}");
+            assert_eq!(Waveform::from_key(waveform.key_as_str()), Some(waveform));
+        }
+        assert_eq!(Waveform::Square.sample(1.25), 1.0);
+    }
+
+    #[test]
+    fn operations_have_voices_and_places() {
+        let base = SortBase::new(vec![1, 3, 5, 2, 4]);
+        let note = |op| Note::of(&op, &base);
+        let swap = note(Op::Swap(0, 4)).unwrap();
+        assert_eq!(swap.voice, Voice::Chord);
+        assert_eq!(swap.fractions, [0.2, 0.8]);
+        assert_eq!(swap.pan, 0.0);
+
+        let write = note(Op::Write(4, 4)).unwrap();
+        assert_eq!((write.voice, write.pan), (Voice::Tone, 1.0));
+        let compare = note(Op::Compare(2, 0)).unwrap();
+        assert_eq!((compare.voice, compare.pan), (Voice::Click, -1.0));
+        assert_eq!(compare.fractions, [0.2]);
+
+        assert_eq!(note(Op::AuxWrite(Aux::Tmp, 0, 3)), None);
+        assert_eq!(
+            Note::of(&Op::Read(0), &SortBase::new(vec![7])).unwrap().pan,
+            0.0
+        );
+    }
+
+    #[test]
+    fn frames_are_thinned_to_the_most_notes() {
+        let notes: Vec<Note> = (0..200)
+            .map(|n| Note {
+                voice: Voice::Tone,
+                fractions: vec![n as f64],
+                pan: 0.0,
+            })
+            .collect();
+        let scheduled: Vec<(f64, &Note, f64)> = schedule(&notes, 0.064).collect();
+        assert_eq!(scheduled.len(), MAX_NOTES);
+        assert_eq!(scheduled[1].1.fractions, [3.0]);
+        assert!((scheduled[1].0 - 0.001).abs() < 1e-12 && (scheduled[1].2 - 0.001).abs() < 1e-12);
+        assert_eq!(schedule(&notes[..2], 0.1).count(), 2);
+        assert_eq!(schedule(&[], 0.1).count(), 0);
+    }
+}
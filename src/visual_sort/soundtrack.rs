@@ -0,0 +1,363 @@
+use super::pitch::Pitch;
+use super::sound::{schedule, Note, Waveform, MAX_SPAN};
+use super::{Sort, SortBase, StepCount, VisualSort};
+use std::f64::consts::FRAC_PI_4;
+
+/// Seconds between the frames steps are taken on, as the animation clock ticks
+const FRAME_SECONDS: f64 = 1.0 / 60.0;
+/// Slowest a run is played, so that every soundtrack comes to an end
+const MIN_STEPS_PER_SECOND: f64 = 1.0;
+
+/// Plays a whole run into samples, the notes pitched and shaped as they are heard live.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Synth {
+    pub sample_rate: u32,
+    /// Steps of the sort played each second, at least one.
+    pub steps_per_second: f64,
+    pub waveform: Waveform,
+    pub pitch: Pitch,
+    /// Loudness of a note at full level, from 0 to 1.
+    pub volume: f64,
+    /// Longest the soundtrack may be, played faster to fit when the run is longer.
+    pub max_seconds: Option<f64>,
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44_100,
+            steps_per_second: 60.0,
+            waveform: Waveform::Sine,
+            pitch: Pitch::default(),
+            volume: 0.5,
+            max_seconds: None,
+        }
+    }
+}
+
+impl Synth {
+    /// The soundtrack of sorting the data from start to finish.
+    ///
+    /// Steps are taken a frame at a time, and the notes of each frame spread over the time
+    /// since the frame before, as the live run does.
+    pub fn render(&self, sort: Sort, base: SortBase) -> Soundtrack {
+        let mut rendering = self.start(sort, base);
+        while !rendering.work(usize::MAX) {}
+        rendering.soundtrack
+    }
+
+    /// A soundtrack rendered a budget of steps at a time, as [`Synth::render`] would.
+    pub fn start(&self, sort: Sort, base: SortBase) -> Rendering {
+        // a first run to know how fast it must be played to fit
+        let counting = self
+            .max_seconds
+            .is_some()
+            .then(|| StepCount::new(sort, base.clone()));
+        Rendering {
+            synth: *self,
+            counting,
+            steps: None,
+            // NaN gives way to the minimum as well
+            steps_per_second: self.steps_per_second.max(MIN_STEPS_PER_SECOND),
+            sorter: sort.init(base),
+            step: 0,
+            frame: 0,
+            last: 0.0,
+            owed: 0.0,
+            notes: Vec::new(),
+            soundtrack: Soundtrack {
+                sample_rate: self.sample_rate.max(1),
+                samples: Vec::new(),
+            },
+        }
+    }
+
+    /// Add a note starting at seconds into the soundtrack, lasting length seconds
+    fn mix(&self, soundtrack: &mut Soundtrack, note: &Note, at: f64, length: f64) {
+        let rate = soundtrack.sample_rate as f64;
+        let start = (at * rate).round() as usize;
+        let count = (length * rate).round() as usize;
+        if soundtrack.samples.len() < 2 * (start + count) {
+            soundtrack.samples.resize(2 * (start + count), 0.0);
+        }
+
+        // equal power, so a note sounds as loud wherever it is placed
+        let angle = (note.pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        let (left, right) = (angle.cos(), angle.sin());
+        let envelope = note.voice.envelope();
+        let level = note.voice.level() * self.volume;
+        let hz: Vec<f64> = note.fractions.iter().map(|&f| self.pitch.hz(f)).collect();
+        for n in 0..count {
+            let t = n as f64 / rate;
+            let wave: f64 = hz.iter().map(|hz| self.waveform.sample(hz * t)).sum();
+            let sample = wave * envelope.level(length, t) * level;
+            let i = 2 * (start + n);
+            soundtrack.samples[i] += (sample * left) as f32;
+            soundtrack.samples[i + 1] += (sample * right) as f32;
+        }
+    }
+}
+
+/// A run being played into a soundtrack, so a page can work on it a little each frame.
+pub struct Rendering {
+    synth: Synth,
+    counting: Option<StepCount>,
+    /// Steps of the whole run, once counted.
+    steps: Option<usize>,
+    steps_per_second: f64,
+    sorter: Box<dyn VisualSort>,
+    step: usize,
+    frame: usize,
+    last: f64,
+    /// Steps due by the current frame and not yet taken.
+    owed: f64,
+    notes: Vec<Note>,
+    soundtrack: Soundtrack,
+}
+
+impl Rendering {
+    /// How far the run is played, from 0 to 1, or None while its steps are counted or
+    /// when they are not counted at all.
+    pub fn progress(&self) -> Option<f64> {
+        match (&self.counting, self.steps) {
+            (None, Some(steps)) => Some(self.step as f64 / steps.max(1) as f64),
+            _ => None,
+        }
+    }
+
+    /// Step up to budget times, true once the run is played to the end.
+    ///
+    /// A frame costs one of the budget as well, so slow runs that take no step in most
+    /// frames still return.
+    pub fn work(&mut self, mut budget: usize) -> bool {
+        if let Some(counting) = self.counting.as_mut() {
+            if let Some(steps) = counting.work(budget) {
+                let max_seconds = self.synth.max_seconds.unwrap_or(f64::INFINITY);
+                self.steps_per_second = self
+                    .steps_per_second
+                    .max(steps as f64 / max_seconds.max(FRAME_SECONDS));
+                self.steps = Some(steps);
+                self.counting = None;
+            }
+            return false;
+        }
+
+        loop {
+            // the frame begun last time, its steps cut short by the budget
+            if self.owed >= 1.0 {
+                while self.owed >= 1.0 && !self.sorter.done() {
+                    if budget == 0 {
+                        return false;
+                    }
+                    self.sorter.update();
+                    let ops = self.sorter.base_mut().take_ops();
+                    let base = self.sorter.base();
+                    self.notes
+                        .extend(ops.iter().filter_map(|op| Note::of(op, base)));
+                    self.owed -= 1.0;
+                    self.step += 1;
+                    budget -= 1;
+                }
+                let now = self.frame as f64 * FRAME_SECONDS;
+                let span = (now - self.last).min(MAX_SPAN);
+                self.last = now;
+                for (at, note, length) in schedule(&self.notes, span) {
+                    self.synth.mix(&mut self.soundtrack, note, now + at, length);
+                }
+                self.notes.clear();
+            }
+            if self.sorter.done() {
+                return true;
+            }
+            if budget == 0 {
+                return false;
+            }
+            budget -= 1;
+            self.frame += 1;
+            self.owed += self.steps_per_second * FRAME_SECONDS;
+        }
+    }
+
+    /// The sound of the run played so far.
+    pub fn soundtrack(self) -> Soundtrack {
+        self.soundtrack
+    }
+}
+
+/// Stereo sound of a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Soundtrack {
+    pub sample_rate: u32,
+    /// Left and right samples in turn, full scale from -1 to 1.
+    pub samples: Vec<f32>,
+}
+
+impl Soundtrack {
+    /// Length in seconds.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / 2.0 / self.sample_rate as f64
+    }
+
+    /// Encode as a 16 bit PCM WAV file, clipping anything louder than full scale.
+    pub fn wav(&self) -> Vec<u8> {
+        const CHANNELS: u16 = 2;
+        const BYTES: u16 = 2;
+        let data = (self.samples.len() * BYTES as usize) as u32;
+        let mut wav = Vec::with_capacity(44 + data as usize);
+        wav.extend(b"RIFF");
+        wav.extend((36 + data).to_le_bytes());
+        wav.extend(b"WAVE");
+
+        wav.extend(b"fmt ");
+        wav.extend(16u32.to_le_bytes());
+        // PCM
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(CHANNELS.to_le_bytes());
+        wav.extend(self.sample_rate.to_le_bytes());
+        wav.extend((self.sample_rate * (CHANNELS * BYTES) as u32).to_le_bytes());
+        wav.extend((CHANNELS * BYTES).to_le_bytes());
+        wav.extend((BYTES * 8).to_le_bytes());
+
+        wav.extend(b"data");
+        wav.extend(data.to_le_bytes());
+        for &sample in &self.samples {
+            let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+            wav.extend(sample.to_le_bytes());
+        }
+        wav
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::sound::Voice;
+
+    #[test]
+    fn wav_has_a_pcm_header() {
+        let soundtrack = Soundtrack {
+            sample_rate: 8000,
+            samples: vec![0.0, 1.0, -1.0, 2.0],
+        };
+        let wav = soundtrack.wav();
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 44);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 8000);
+        assert_eq!(u32::from_le_bytes(wav[28..32].try_into().unwrap()), 32000);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 8);
+        let samples: Vec<i16> = wav[44..]
+            .chunks(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        // louder than full scale is clipped
+        assert_eq!(samples, [0, i16::MAX, -i16::MAX, i16::MAX]);
+        assert_eq!(soundtrack.duration(), 0.00025);
+    }
+
+    #[test]
+    fn run_is_heard_at_the_chosen_rate() {
+        let synth = Synth {
+            sample_rate: 8000,
+            steps_per_second: 120.0,
+            ..Synth::default()
+        };
+        let base = SortBase::new(vec![5, 3, 8, 1, 9, 2, 7, 4, 6]);
+        let steps = Sort::Insertion.count_steps(base.clone());
+        let soundtrack = synth.render(Sort::Insertion, base.clone());
+        let seconds = steps as f64 / 120.0;
+        assert!((soundtrack.duration() - seconds).abs() < 2.0 * FRAME_SECONDS);
+        assert!(soundtrack.samples.iter().any(|&s| s.abs() > 0.1));
+        assert!(soundtrack.samples.iter().all(|&s| s.abs() <= 1.0));
+
+        // too long a run is played faster to fit
+        let fitted = Synth {
+            max_seconds: Some(seconds / 4.0),
+            ..synth
+        }
+        .render(Sort::Insertion, base);
+        assert!(fitted.duration() <= seconds / 4.0 + 2.0 * FRAME_SECONDS);
+    }
+
+    #[test]
+    fn rendering_in_small_budgets_matches_the_whole_render() {
+        let synth = Synth {
+            sample_rate: 8000,
+            steps_per_second: 30.0,
+            max_seconds: Some(1.0),
+            ..Synth::default()
+        };
+        let base = SortBase::new(vec![5, 3, 8, 1, 9, 2, 7, 4, 6]);
+        for sort in Sort::ALL {
+            let whole = synth.render(sort, base.clone());
+            let mut rendering = synth.start(sort, base.clone());
+            assert_eq!(rendering.progress(), None);
+            let mut progress = 0.0;
+            while !rendering.work(3) {
+                if let Some(now) = rendering.progress() {
+                    assert!(now >= progress && now <= 1.0, "{sort:?}");
+                    progress = now;
+                }
+            }
+            assert_eq!(rendering.progress(), Some(1.0));
+            assert_eq!(rendering.soundtrack(), whole, "{sort:?}");
+        }
+    }
+
+    #[test]
+    fn too_slow_a_rate_still_comes_to_an_end() {
+        let base = SortBase::new(vec![3, 1, 2]);
+        let steps = Sort::Insertion.count_steps(base.clone());
+        for steps_per_second in [0.0, -5.0, f64::NAN] {
+            let synth = Synth {
+                sample_rate: 100,
+                steps_per_second,
+                ..Synth::default()
+            };
+            let mut rendering = synth.start(Sort::Insertion, base.clone());
+            let mut calls = 0;
+            while !rendering.work(10) {
+                calls += 1;
+                assert!(
+                    calls < 1000,
+                    "no end at {steps_per_second} steps per second"
+                );
+            }
+            // played at the slowest rate, the last note heard out
+            let seconds = steps as f64 / MIN_STEPS_PER_SECOND;
+            let duration = rendering.soundtrack().duration();
+            assert!(duration > seconds - 1.0 && duration <= seconds + MAX_SPAN);
+        }
+    }
+
+    #[test]
+    fn notes_are_placed_left_to_right() {
+        let synth = Synth {
+            sample_rate: 8000,
+            ..Synth::default()
+        };
+        let mut soundtrack = Soundtrack {
+            sample_rate: 8000,
+            samples: Vec::new(),
+        };
+        let note = Note {
+            voice: Voice::Tone,
+            fractions: vec![0.5],
+            pan: -1.0,
+        };
+        synth.mix(&mut soundtrack, &note, 0.0, 0.1);
+        assert_eq!(soundtrack.samples.len(), 2 * 800);
+        let loudness = |channel: usize| -> f32 {
+            soundtrack.samples[channel..]
+                .iter()
+                .step_by(2)
+                .map(|s| s.abs())
+                .sum()
+        };
+        assert!(loudness(0) > 0.0);
+        assert!(loudness(1) < 1e-3 * loudness(0));
+    }
+}
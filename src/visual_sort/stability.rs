@@ -0,0 +1,173 @@
+use super::{Color, Scale, SortBase};
+
+/// Distinct keys in generated stability input
+pub const KEYS: usize = 8;
+
+/// Shades telling elements of the same key apart, from first to last in the input
+pub const SHADES: [&str; 5] = ["#f1aeb5", "#ea868f", "#e35d6a", "#b02a37", "#58151c"];
+
+/// Reduce data to KEYS distinct keys, keeping its shape.
+pub fn keys(data: &[usize]) -> Vec<usize> {
+    let scale = Scale::new(data);
+    // in u64, as large values times KEYS outgrow a 32 bit usize
+    let range = (scale.max - scale.min) as u64 + 1;
+    data.iter()
+        .map(|&v| ((v - scale.min) as u64 * KEYS as u64 / range) as usize + 1)
+        .collect()
+}
+
+/// Replace values by their rank among the distinct values, keeping which are equal.
+///
+/// Keeps tagged values small, as they grow with the largest key times the number of items.
+pub fn ranks(data: &[usize]) -> Vec<usize> {
+    let mut distinct = data.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    data.iter()
+        .map(|v| distinct.binary_search(v).expect("value to be in data") + 1)
+        .collect()
+}
+
+/// Whether keys can carry their index, a tagged value being up to the largest key plus one
+/// times the number of items.
+pub fn fits_tagged(keys: &[usize]) -> bool {
+    let largest = keys.iter().copied().max().unwrap_or(0);
+    largest
+        .checked_add(1)
+        .and_then(|key| key.checked_mul(keys.len()))
+        .is_some()
+}
+
+/// Keys of input that already has its duplicates, its ranks where they fit tagged and
+/// KEYS keys of the same shape otherwise.
+pub fn custom_keys(data: &[usize]) -> Vec<usize> {
+    let ranks = ranks(data);
+    if fits_tagged(&ranks) {
+        ranks
+    } else {
+        keys(data)
+    }
+}
+
+/// Whether elements of equal keys are still in their original order.
+pub fn is_stable(data: &[usize], scale: Scale) -> bool {
+    data.windows(2)
+        .all(|w| scale.key(w[0]) != scale.key(w[1]) || scale.tag(w[0]) < scale.tag(w[1]))
+}
+
+/// Shade of each tagged element by where it came within its key in the input.
+pub struct Shades {
+    shades: Vec<u8>,
+    scale: Scale,
+}
+
+impl Shades {
+    /// None for data that is not tagged.
+    pub fn new(base: &SortBase) -> Option<Self> {
+        let scale = base.scale();
+        if !scale.is_tagged() {
+            return None;
+        }
+        let data = base.data();
+        let mut totals = vec![0; scale.max + 1];
+        for &value in data {
+            totals[scale.key(value)] += 1;
+        }
+        // tags are the original indices, so rank values by their tag
+        let mut by_tag = data.to_vec();
+        by_tag.sort_unstable_by_key(|&value| scale.tag(value));
+        let mut seen = vec![0; scale.max + 1];
+        let shades = by_tag
+            .into_iter()
+            .map(|value| {
+                let key = scale.key(value);
+                let shade = seen[key] * SHADES.len() / totals[key];
+                seen[key] += 1;
+                shade as u8
+            })
+            .collect();
+        Some(Self { shades, scale })
+    }
+
+    pub fn color(&self, value: usize) -> Color {
+        Color::Shade(self.shades[self.scale.tag(value)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visual_sort::tests::{inputs, run};
+    use crate::visual_sort::Sort;
+
+    /// Sort tagged keys, returning the data and its scale
+    fn sorted_keys(sort: Sort, keys: &[usize]) -> (Vec<usize>, Scale) {
+        let mut sorter = sort.init(SortBase::tagged(keys.to_vec()));
+        run(sorter.as_mut());
+        let (data, scale) = (sorter.base().data().to_vec(), sorter.base().scale());
+        let mut expected = keys.to_vec();
+        expected.sort_unstable();
+        let sorted: Vec<usize> = data.iter().map(|&v| scale.key(v)).collect();
+        assert_eq!(sorted, expected, "{sort:?} on {keys:?}");
+        (data, scale)
+    }
+
+    #[test]
+    fn stable_sorts_keep_equal_keys_in_order() {
+        for keys in inputs().iter().map(|data| keys(data)) {
+            for sort in [Sort::Bubble, Sort::Insertion, Sort::Merge, Sort::Radix] {
+                let (data, scale) = sorted_keys(sort, &keys);
+                assert!(is_stable(&data, scale), "{sort:?} on {keys:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn unstable_sorts_scramble_equal_keys() {
+        let inputs: Vec<Vec<usize>> = inputs().iter().map(|data| keys(data)).collect();
+        for sort in [Sort::Heap, Sort::Quick, Sort::Selection] {
+            let scrambled = inputs.iter().any(|keys| {
+                let (data, scale) = sorted_keys(sort, keys);
+                !is_stable(&data, scale)
+            });
+            assert!(scrambled, "{sort:?}");
+        }
+        // counting rebuilds values from the keys, the elements themselves are gone
+        let (data, scale) = sorted_keys(Sort::Counting, &[2, 1, 2, 1]);
+        assert!(data.iter().all(|&v| scale.tag(v) == 0));
+    }
+
+    #[test]
+    fn shades_follow_input_order_within_keys() {
+        let base = SortBase::tagged(vec![3, 1, 3, 3, 1]);
+        let shades = Shades::new(&base).unwrap();
+        let colors: Vec<u8> = base
+            .data()
+            .iter()
+            .map(|&v| match shades.color(v) {
+                Color::Shade(shade) => shade,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(colors, [0, 0, 1, 3, 2]);
+        assert!(Shades::new(&SortBase::new(vec![3, 1])).is_none());
+    }
+
+    #[test]
+    fn keys_keep_the_shape() {
+        assert_eq!(keys(&[0, 99, 50, 50]), [1, 8, 5, 5]);
+        assert_eq!(keys(&[7, 7]), [1, 1]);
+        assert_eq!(ranks(&[1_000_000, 5, 5, 70]), [3, 1, 1, 2]);
+        assert_eq!(keys(&[0, u32::MAX as usize]), [1, 8]);
+    }
+
+    #[test]
+    fn tags_fit_below_the_largest_value() {
+        assert!(fits_tagged(&[]));
+        assert!(fits_tagged(&[KEYS; 1000]));
+        assert!(fits_tagged(&[usize::MAX / 3 - 1, 0, 1]));
+        assert!(!fits_tagged(&[usize::MAX / 3, 0, 1]));
+        assert!(!fits_tagged(&[usize::MAX]));
+        assert_eq!(custom_keys(&[usize::MAX, 0, 5]), [3, 1, 2]);
+    }
+}
@@ -0,0 +1,225 @@
+use super::input::{self, Distribution};
+use super::{Color, Op, Sort, SortBase, StepCount, VisualSort};
+use rand::Rng;
+
+const MAX_ITEMS: usize = 64;
+
+/// Inputs of every size up to MAX_ITEMS, in every distribution and a few more shapes
+pub(super) fn inputs() -> Vec<Vec<usize>> {
+    let mut rng = input::rng(0x5027);
+    let mut inputs = vec![Vec::new()];
+    for n in 1..=MAX_ITEMS {
+        for distribution in Distribution::ALL {
+            inputs.push(distribution.generate(n, n as u64));
+        }
+        inputs.push(vec![7; n]);
+        inputs.push((0..n).map(|_| rng.gen_range(0..4)).collect());
+        inputs.push((0..n).map(|_| rng.gen_range(0..100_000)).collect());
+    }
+    inputs
+}
+
+/// Step a sort until done, returning every operation it performed
+pub(super) fn run(sorter: &mut dyn VisualSort) -> Vec<Op> {
+    let n = sorter.base().data().len();
+    let limit = 4 * n * n + 1000;
+    let mut ops = sorter.base_mut().take_ops();
+    let mut steps = 0;
+    while !sorter.done() {
+        assert!(steps < limit, "no progress after {steps} steps");
+        sorter.update();
+        ops.append(&mut sorter.base_mut().take_ops());
+        steps += 1;
+    }
+    ops
+}
+
+fn sorted(data: &[usize]) -> Vec<usize> {
+    let mut data = data.to_vec();
+    data.sort_unstable();
+    data
+}
+
+#[test]
+fn sorts_every_input() {
+    for sort in Sort::ALL {
+        for input in inputs() {
+            let mut sorter = sort.init(SortBase::new(input.clone()));
+            run(sorter.as_mut());
+            assert_eq!(
+                sorter.base().data(),
+                sorted(&input),
+                "{} on {input:?}",
+                sort.name_as_str(),
+            );
+        }
+    }
+}
+
+#[test]
+fn ops_replay_to_sorted_data() {
+    for sort in Sort::ALL {
+        for input in inputs() {
+            let mut sorter = sort.init(SortBase::new(input.clone()));
+            let mut replay = input.clone();
+            for op in run(sorter.as_mut()) {
+                match op {
+                    Op::Swap(i, j) => replay.swap(i, j),
+                    Op::Write(i, value) => replay[i] = value,
+                    _ => (),
+                }
+            }
+            assert_eq!(replay, sorter.base().data(), "{}", sort.name_as_str());
+        }
+    }
+}
+
+#[test]
+fn ops_stay_in_bounds() {
+    for sort in Sort::ALL {
+        for input in inputs() {
+            let n = input.len();
+            let mut sorter = sort.init(SortBase::new(input));
+            for op in run(sorter.as_mut()) {
+                let in_bounds = match op {
+                    Op::Read(i) | Op::Write(i, _) => i < n,
+                    Op::Compare(i, j) | Op::Swap(i, j) => i < n && j < n,
+                    Op::AuxRead(..) | Op::AuxWrite(..) | Op::AuxCompare(..) => true,
+                };
+                assert!(in_bounds, "{} did {op:?} on {n} items", sort.name_as_str());
+            }
+        }
+    }
+}
+
+#[test]
+fn done_sort_stays_done() {
+    for sort in Sort::ALL {
+        let mut sorter = sort.init(SortBase::new(input::shuffled(MAX_ITEMS, 0)));
+        run(sorter.as_mut());
+        let data = sorter.base().data().to_vec();
+        sorter.update();
+        assert!(sorter.done());
+        assert_eq!(sorter.base().data(), data, "{}", sort.name_as_str());
+    }
+}
+
+#[test]
+fn steps_are_counted_alike_in_any_budget() {
+    for sort in Sort::ALL {
+        let input = input::shuffled(MAX_ITEMS, 0);
+        let mut sorter = sort.init(SortBase::new(input.clone()));
+        let mut steps = 0;
+        while !sorter.done() {
+            sorter.update();
+            steps += 1;
+        }
+        assert_eq!(sort.count_steps(SortBase::new(input.clone())), steps);
+
+        let mut count = StepCount::new(sort, SortBase::new(input));
+        let mut calls = 1;
+        while count.work(7).is_none() {
+            calls += 1;
+        }
+        assert_eq!(count.steps(), steps, "{}", sort.name_as_str());
+        assert_eq!(calls, steps.div_ceil(7).max(1), "{}", sort.name_as_str());
+    }
+    assert_eq!(Sort::Merge.count_steps(SortBase::new(Vec::new())), 0);
+}
+
+#[test]
+fn sorts_are_found_by_route() {
+    for sort in Sort::ALL {
+        let route = sort.route_as_str();
+        assert_eq!(Sort::from_route(route), Some(sort));
+        assert_eq!(Sort::from_route(&route[1..]), Some(sort));
+    }
+    assert_eq!(Sort::from_route("/benchmark"), None);
+    assert_eq!(Sort::from_route(""), None);
+}
+
+#[test]
+fn empty_data_is_already_sorted() {
+    for sort in Sort::ALL {
+        let mut sorter = sort.init(SortBase::new(Vec::new()));
+        assert!(sorter.done(), "{}", sort.name_as_str());
+        sorter.update();
+        assert!(sorter.done(), "{}", sort.name_as_str());
+        assert!(sorter.base().data().is_empty());
+        for i in 0..2 {
+            sorter.color(i);
+        }
+    }
+}
+
+#[test]
+fn steps_report_lines_of_the_pseudocode() {
+    for sort in Sort::ALL {
+        let lines = sort.pseudocode().len();
+        for input in inputs() {
+            let mut sorter = sort.init(SortBase::new(input));
+            while !sorter.done() {
+                sorter.update();
+                assert!(sorter.line() < lines, "{}", sort.name_as_str());
+            }
+        }
+    }
+}
+
+#[test]
+fn every_line_of_the_pseudocode_is_reached() {
+    for sort in Sort::ALL {
+        let mut reached = vec![false; sort.pseudocode().len()];
+        for input in inputs() {
+            let mut sorter = sort.init(SortBase::new(input));
+            while !sorter.done() {
+                sorter.update();
+                reached[sorter.line()] = true;
+            }
+        }
+        let missed: Vec<usize> = (0..reached.len()).filter(|&l| !reached[l]).collect();
+        assert!(
+            missed.is_empty(),
+            "{} never on {missed:?}",
+            sort.name_as_str()
+        );
+    }
+}
+
+#[test]
+fn highlights_cover_every_colored_index() {
+    for sort in Sort::ALL {
+        for input in inputs() {
+            let n = input.len();
+            let mut sorter = sort.init(SortBase::new(input));
+            while !sorter.done() {
+                sorter.update();
+                let highlights = sorter.highlights();
+                for i in (0..n).filter(|&i| sorter.color(i) != Color::Red) {
+                    assert!(highlights.contains(&i), "{} at {i}", sort.name_as_str());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn aux_buffers_fit_in_aux_memory() {
+    for sort in Sort::ALL {
+        for input in inputs() {
+            let mut sorter = sort.init(SortBase::new(input));
+            while !sorter.done() {
+                sorter.update();
+                let buffers = sorter.aux();
+                let held: usize = buffers.iter().map(|b| b.values.len()).sum();
+                assert!(held <= sorter.aux_len(), "{}", sort.name_as_str());
+                for buffer in buffers {
+                    assert!(buffer.cursor.is_none_or(|c| c <= buffer.values.len()));
+                }
+                if sort.in_place() {
+                    assert_eq!(sorter.aux_len(), 0, "{}", sort.name_as_str());
+                }
+            }
+        }
+    }
+}